@@ -1,14 +1,20 @@
 // Copyright (C) 2025 Andrew Wason
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+mod logging;
 mod plugin;
 mod renderer;
 
 pub use frei0r_rs2;
+pub use logging::{WEBVFX_LOG_ENV, init_logging};
 pub use plugin::{
-    filter::FilterPlugin, mixer2::Mixer2Plugin, mixer3::Mixer3Plugin, source::SourcePlugin,
+    filter::FilterPlugin, mixer2::Mixer2Plugin, mixer3::Mixer3Plugin, param_index,
+    source::SourcePlugin,
 };
 pub use renderer::{
-    WEBVFX_CSS_ANIMATION_PROPERTY, WEBVFX_SELECTOR_PREFIX, net::SyncNetProvider,
-    processor::process_template,
+    WEBVFX_CSS_ANIMATION_PROPERTY, WEBVFX_SELECTOR_PREFIX,
+    net::SyncNetProvider,
+    params::{ParamDeclaration, ParamType, ParamValue, param_declarations_json},
+    processor::{TemplateData, process_template, process_template_source},
+    watch::FileWatcher,
 };
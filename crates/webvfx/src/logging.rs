@@ -0,0 +1,172 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    sync::{Mutex, Once},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Environment variable configuring logging, a comma separated list of a level
+/// (`off`, `error`, `warn`, `info`, `debug` or `trace`) and `file=<path>` to append to a file
+/// instead of stderr. e.g. `WEBVFX_LOG=debug,file=/tmp/webvfx.log`
+pub const WEBVFX_LOG_ENV: &str = "WEBVFX_LOG";
+
+/// Level applied to log records from other crates
+const DEPENDENCY_LEVEL: LevelFilter = LevelFilter::Warn;
+
+/// Install the logger, this is safe to call any number of times.
+/// If `WEBVFX_LOG` is not set and the `tracing` feature is enabled,
+/// a `tracing_subscriber` is installed instead.
+pub fn init_logging() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let spec = std::env::var(WEBVFX_LOG_ENV).ok();
+
+        #[cfg(feature = "tracing")]
+        if spec.is_none() {
+            // The host or another plugin may already have installed a subscriber
+            let _ = tracing_subscriber::fmt::try_init();
+            return;
+        }
+
+        let config = LogConfig::parse(spec.as_deref().unwrap_or_default());
+        let output = match &config.file {
+            Some(path) => match OpenOptions::new().create(true).append(true).open(path) {
+                Ok(file) => Output::File(file),
+                Err(e) => {
+                    eprintln!(
+                        "WebVfx: failed to open log file '{}', logging to stderr: {e}",
+                        path.display()
+                    );
+                    Output::Stderr
+                }
+            },
+            None => Output::Stderr,
+        };
+        let logger = Logger {
+            level: config.level,
+            output: Mutex::new(output),
+        };
+        if log::set_boxed_logger(Box::new(logger)).is_ok() {
+            log::set_max_level(config.level.max(DEPENDENCY_LEVEL));
+        }
+    });
+}
+
+#[derive(Debug, PartialEq)]
+struct LogConfig {
+    level: LevelFilter,
+    file: Option<PathBuf>,
+}
+
+impl LogConfig {
+    fn parse(spec: &str) -> Self {
+        let mut config = LogConfig {
+            level: LevelFilter::Warn,
+            file: None,
+        };
+        for item in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            if let Some(path) = item.strip_prefix("file=") {
+                config.file = Some(PathBuf::from(path));
+            } else if let Ok(level) = item.parse() {
+                config.level = level;
+            } else {
+                eprintln!("WebVfx: ignoring invalid {WEBVFX_LOG_ENV} setting '{item}'");
+            }
+        }
+        config
+    }
+}
+
+enum Output {
+    Stderr,
+    File(File),
+}
+
+struct Logger {
+    level: LevelFilter,
+    output: Mutex<Output>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let level = if metadata.target().starts_with(env!("CARGO_CRATE_NAME")) {
+            self.level
+        } else {
+            DEPENDENCY_LEVEL
+        };
+        metadata.level() <= level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let line = format!(
+            "{}.{:03} {:<5} {}: {}\n",
+            timestamp.as_secs(),
+            timestamp.subsec_millis(),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        let Ok(mut output) = self.output.lock() else {
+            return;
+        };
+        let _ = match &mut *output {
+            Output::Stderr => io::stderr().write_all(line.as_bytes()),
+            Output::File(file) => file.write_all(line.as_bytes()),
+        };
+    }
+
+    fn flush(&self) {
+        if let Ok(mut output) = self.output.lock() {
+            let _ = match &mut *output {
+                Output::Stderr => io::stderr().flush(),
+                Output::File(file) => file.flush(),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        assert_eq!(
+            LogConfig::parse(""),
+            LogConfig {
+                level: LevelFilter::Warn,
+                file: None
+            }
+        );
+        assert_eq!(
+            LogConfig::parse("debug,file=/tmp/webvfx.log"),
+            LogConfig {
+                level: LevelFilter::Debug,
+                file: Some(PathBuf::from("/tmp/webvfx.log"))
+            }
+        );
+        assert_eq!(
+            LogConfig::parse("file=webvfx.log, TRACE, bogus"),
+            LogConfig {
+                level: LevelFilter::Trace,
+                file: Some(PathBuf::from("webvfx.log"))
+            }
+        );
+    }
+}
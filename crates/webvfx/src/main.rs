@@ -4,6 +4,8 @@ use std::{
     path::{self, Path, PathBuf},
     process::exit,
     sync::Arc,
+    thread,
+    time::Duration,
 };
 
 use argh::FromArgs;
@@ -14,9 +16,16 @@ use blitz_shell::{
 };
 use blitz_traits::net::Url;
 use webvfx::{
-    SyncNetProvider, WEBVFX_CSS_ANIMATION_PROPERTY, WEBVFX_SELECTOR_PREFIX, process_template,
+    FileWatcher, ParamDeclaration, SyncNetProvider, TemplateData, WEBVFX_CSS_ANIMATION_PROPERTY,
+    WEBVFX_SELECTOR_PREFIX, param_declarations_json, process_template, process_template_source,
+};
+use winit::{
+    application::ApplicationHandler,
+    dpi::LogicalSize,
+    event::{StartCause, WindowEvent},
+    event_loop::{ActiveEventLoop, EventLoopProxy},
+    window::WindowId,
 };
-use winit::dpi::LogicalSize;
 
 #[derive(FromArgs)]
 #[argh(help_triggers("-h", "--help", "help"))]
@@ -43,59 +52,65 @@ struct Args {
     /// JSON path to template data
     json: Option<String>,
 
+    #[argh(option)]
+    /// JSON template data, takes precedence over --json
+    json_data: Option<String>,
+
+    #[argh(option)]
+    /// base URL or directory for relative URLs when reading HTML from stdin
+    base_url: Option<String>,
+
+    #[argh(switch)]
+    /// print the params declared by the HTML as JSON and exit
+    list_params: bool,
+
     #[argh(positional)]
-    /// path to HTML file
+    /// path to HTML file, or - to read HTML from stdin
     html: String,
 }
 
 fn main() {
-    #[cfg(feature = "tracing")]
-    tracing_subscriber::fmt::init();
+    webvfx::init_logging();
 
     let args: Args = argh::from_env();
 
-    let (url, html) = match process_template(&args.html, args.json) {
-        Ok(result) => result,
+    let html_source = if args.html == "-" {
+        match std::io::read_to_string(std::io::stdin()) {
+            Ok(html) => Some(html),
+            Err(e) => {
+                eprintln!("Failed to read HTML from stdin: {e}");
+                exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    let template_data = match (&args.json_data, &args.json) {
+        (Some(json_data), _) => Some(TemplateData::Json(json_data.clone())),
+        (None, Some(json)) => Some(TemplateData::Path(PathBuf::from(json))),
+        (None, None) => None,
+    };
+    let viewer = Viewer {
+        html_source,
+        template_data,
+        net_provider: Arc::new(SyncNetProvider::new()),
+        args,
+    };
+
+    let document = match viewer.load_document() {
+        Ok(document) => document,
         Err(e) => {
             eprintln!("{e}");
             exit(1);
         }
     };
 
-    let mut document = HtmlDocument::from_html(
-        &html,
-        DocumentConfig {
-            base_url: Some(url.into()),
-            ua_stylesheets: Some(vec![format!(
-                r"
-                :root {{
-                    {WEBVFX_CSS_ANIMATION_PROPERTY}: {}
-                }}
-                ",
-                args.animation_duration
-            )]),
-            net_provider: Some(Arc::new(SyncNetProvider::new())),
-            ..Default::default()
-        },
-    );
-
-    for (index, image) in args.image.iter().enumerate() {
-        let selector = format!("{}{}", WEBVFX_SELECTOR_PREFIX, index + 1);
-        if let Ok(node_ids) = document.query_selector_all(&selector)
-            && !node_ids.is_empty()
-        {
-            if let Ok((url, _)) = path_url(image) {
-                node_ids.iter().copied().for_each(|node_id| {
-                    document
-                        .mutate()
-                        .set_attribute(node_id, qual_name!("src"), url.as_str());
-                });
-            } else {
-                eprintln!("Invalid image path '{image}', ignoring");
-            }
-        } else {
-            eprintln!("Selector {selector} not found in document");
-        }
+    if viewer.args.list_params {
+        println!(
+            "{}",
+            param_declarations_json(&ParamDeclaration::from_document(&document))
+        );
+        return;
     }
 
     let renderer = anyrender_vello::VelloWindowRenderer::new();
@@ -104,17 +119,166 @@ fn main() {
         renderer,
         #[allow(clippy::cast_precision_loss)]
         Window::default_attributes()
-            .with_inner_size(LogicalSize::new(args.width as f64, args.height as f64))
+            .with_inner_size(LogicalSize::new(
+                viewer.args.width as f64,
+                viewer.args.height as f64,
+            ))
             .with_title("WebVfx Viewer"),
     );
 
     let event_loop = create_default_event_loop::<BlitzShellEvent>();
-    let mut application = BlitzApplication::new(event_loop.create_proxy());
-    application.add_window(window);
+    viewer.watch(event_loop.create_proxy());
+    let mut application = ViewerApplication {
+        inner: BlitzApplication::new(event_loop.create_proxy()),
+        viewer,
+    };
+    application.inner.add_window(window);
 
     event_loop.run_app(&mut application).unwrap();
 }
 
+struct Viewer {
+    args: Args,
+    html_source: Option<String>,
+    template_data: Option<TemplateData>,
+    net_provider: Arc<SyncNetProvider>,
+}
+
+impl Viewer {
+    fn load_document(&self) -> anyhow::Result<HtmlDocument> {
+        let (url, html) = if let Some(html_source) = &self.html_source {
+            process_template_source(
+                html_source,
+                self.args.base_url.as_deref(),
+                self.template_data.as_ref(),
+            )?
+        } else {
+            process_template(&self.args.html, self.template_data.as_ref())?
+        };
+
+        let mut document = HtmlDocument::from_html(
+            &html,
+            DocumentConfig {
+                base_url: Some(url.into()),
+                ua_stylesheets: Some(vec![format!(
+                    r"
+                    :root {{
+                        {WEBVFX_CSS_ANIMATION_PROPERTY}: {}
+                    }}
+                    ",
+                    self.args.animation_duration
+                )]),
+                net_provider: Some(self.net_provider.clone()),
+                ..Default::default()
+            },
+        );
+
+        for (index, image) in self.args.image.iter().enumerate() {
+            let selector = format!("{}{}", WEBVFX_SELECTOR_PREFIX, index + 1);
+            if let Ok(node_ids) = document.query_selector_all(&selector)
+                && !node_ids.is_empty()
+            {
+                if let Ok((url, _)) = path_url(image) {
+                    node_ids.iter().copied().for_each(|node_id| {
+                        document
+                            .mutate()
+                            .set_attribute(node_id, qual_name!("src"), url.as_str());
+                    });
+                } else {
+                    eprintln!("Invalid image path '{image}', ignoring");
+                }
+            } else {
+                eprintln!("Selector {selector} not found in document");
+            }
+        }
+
+        Ok(document)
+    }
+
+    /// Poll the HTML, JSON and all local files the document fetched,
+    /// and request a reload when any of them change.
+    fn watch(&self, proxy: EventLoopProxy<BlitzShellEvent>) {
+        let mut watcher = FileWatcher::new();
+        if self.html_source.is_none()
+            && let Ok((_, html_path)) = path_url(&self.args.html)
+        {
+            watcher.watch(html_path);
+        }
+        if let Some(TemplateData::Path(json_path)) = &self.template_data {
+            watcher.watch(json_path);
+        }
+        let net_provider = self.net_provider.clone();
+        thread::spawn(move || {
+            loop {
+                thread::sleep(WATCH_INTERVAL);
+                watcher.watch_all(net_provider.fetched_files());
+                if watcher.changed()
+                    && proxy
+                        .send_event(BlitzShellEvent::Embedder(Arc::new(ReloadEvent)))
+                        .is_err()
+                {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+struct ReloadEvent;
+
+struct ViewerApplication {
+    inner: BlitzApplication<anyrender_vello::VelloWindowRenderer>,
+    viewer: Viewer,
+}
+
+impl ViewerApplication {
+    fn reload_document(&mut self) {
+        match self.viewer.load_document() {
+            Ok(document) => {
+                // The view keeps its animation clock, so playback continues from the current time
+                if let Some(view) = self.inner.windows.values_mut().next() {
+                    view.replace_document(Box::new(document) as _, true);
+                }
+            }
+            Err(e) => eprintln!("Failed to reload document: {e}"),
+        }
+    }
+}
+
+impl ApplicationHandler<BlitzShellEvent> for ViewerApplication {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        self.inner.resumed(event_loop);
+    }
+
+    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        self.inner.suspended(event_loop);
+    }
+
+    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: StartCause) {
+        self.inner.new_events(event_loop, cause);
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        self.inner.window_event(event_loop, window_id, event);
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: BlitzShellEvent) {
+        match event {
+            BlitzShellEvent::Embedder(event) if event.is::<ReloadEvent>() => {
+                self.reload_document();
+            }
+            event => self.inner.user_event(event_loop, event),
+        }
+    }
+}
+
 fn path_url(path: &str) -> anyhow::Result<(Url, PathBuf)> {
     let path = path::absolute(Path::new(path))?;
     let url = Url::from_file_path(&path)
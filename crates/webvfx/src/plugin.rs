@@ -3,23 +3,90 @@
 
 use std::{
     ffi::{CStr, CString},
+    fmt::Write,
     marker::PhantomData,
+    path::PathBuf,
 };
 
-use crate::renderer::processor::RenderProcessor;
+use anyhow::Context;
+
+use crate::renderer::{
+    WEBVFX_SELECTOR_PREFIX,
+    easing::Easing,
+    error::render_error_frame,
+    params::{ParamValues, param_declarations_json},
+    post::PostChain,
+    processor::{DocumentInfo, RenderProcessor, ResourcePaths, TemplateData},
+    timing::{TimeDirection, TimeParams},
+    watch::FileWatcher,
+};
 
 pub mod filter;
 pub mod mixer2;
 pub mod mixer3;
 pub mod source;
 
+/// Indices of the params hosts address by index, see `impl_plugin`
+pub mod param_index {
+    use std::ffi::c_int;
+
+    pub const HTML_PATH: c_int = 0;
+    pub const JSON_PATH: c_int = 1;
+    pub const DURATION: c_int = 2;
+    pub const PARAM1: c_int = 3;
+    pub const COLOR1: c_int = 11;
+    pub const POSITION1: c_int = 15;
+    pub const BOOL1: c_int = 19;
+    pub const PARAM_DECLARATIONS: c_int = 23;
+    pub const HTML_SOURCE: c_int = 24;
+    pub const BASE_URL: c_int = 25;
+    pub const JSON_DATA: c_int = 26;
+    pub const ON_ERROR: c_int = 27;
+    pub const STATUS: c_int = 28;
+    pub const LAST_ERROR: c_int = 29;
+    pub const CLIP_DURATION: c_int = 30;
+    pub const FRAME_RATE: c_int = 31;
+
+    /// Params only mixer2 has, they follow `FRAME_RATE`
+    pub mod mixer2 {
+        use std::ffi::c_int;
+
+        pub const POSITION: c_int = 32;
+        pub const EASING: c_int = 33;
+        pub const SCRUB_ANIMATIONS: c_int = 34;
+    }
+}
+
 pub struct WebVfxPlugin<K: frei0r_rs2::PluginKind, const S: usize> {
     html_path: CString,
+    html_source: CString,
+    base_url: CString,
     json_path: CString,
+    json_data: CString,
+    timeline_path: CString,
+    subtitle_path: CString,
+    telemetry_path: CString,
+    lut_path: CString,
     animation_duration: CString,
+    params: ParamValues,
+    /// Defaults of the params the document declares, returned for slots the host has not set
+    param_defaults: ParamValues,
+    time_params: TimeParams,
+    easing: CString,
+    time_direction: CString,
+    post: CString,
+    /// Parsed `post`, replaces any post-processing declared by the document
+    post_chain: Option<PostChain>,
+    param_declarations: CString,
+    status: CString,
+    last_error: CString,
+    on_error: CString,
+    error_frame: Option<(String, Vec<u32>)>,
     width: u32,
     height: u32,
-    processor: Option<Result<RenderProcessor<S>, ()>>,
+    /// On failure, the error and a watcher of the files the processor is created from,
+    /// so creation is retried when they change
+    processor: Option<Result<RenderProcessor<S>, (String, FileWatcher)>>,
     _phantom: PhantomData<K>,
 }
 
@@ -30,8 +97,27 @@ where
     fn new(width: u32, height: u32) -> Self {
         Self {
             html_path: c"".to_owned(),
+            html_source: c"".to_owned(),
+            base_url: c"".to_owned(),
             json_path: c"".to_owned(),
+            json_data: c"".to_owned(),
+            timeline_path: c"".to_owned(),
+            subtitle_path: c"".to_owned(),
+            telemetry_path: c"".to_owned(),
+            lut_path: c"".to_owned(),
             animation_duration: c"5s".to_owned(),
+            params: ParamValues::default(),
+            param_defaults: ParamValues::default(),
+            time_params: TimeParams::default(),
+            easing: c"linear".to_owned(),
+            time_direction: c"forward".to_owned(),
+            post: c"".to_owned(),
+            post_chain: None,
+            param_declarations: c"[]".to_owned(),
+            status: c"not loaded".to_owned(),
+            last_error: c"".to_owned(),
+            on_error: c"error_frame".to_owned(),
+            error_frame: None,
             width,
             height,
             processor: None,
@@ -40,103 +126,515 @@ where
     }
 
     fn update(&mut self, time: f64, inframes: [&[u32]; S], outframe: &mut [u32]) {
+        if let Some(Err((_, watcher))) = &mut self.processor
+            && watcher.changed()
+        {
+            log::info!("source files changed, retrying renderer creation");
+            self.processor = None;
+        }
         let processor = match self.processor {
-            Some(Ok(ref processor)) => processor,
-            Some(Err(())) => return,
-            None => match (self.html_path.to_str(), self.json_path.to_str()) {
-                (Ok(html_path), Ok(json_path)) => {
-                    let json_path = if json_path.is_empty() {
-                        None
-                    } else {
-                        Some(json_path)
-                    };
-
-                    let animation_duration = self.animation_duration.to_str().unwrap_or("5s");
-                    let processor = RenderProcessor::<S>::new(
-                        html_path,
-                        json_path,
-                        animation_duration,
-                        self.width,
-                        self.height,
-                    );
-                    match processor {
-                        Err(e) => {
-                            eprintln!("WebVfx: failed to create renderer: {e:?}");
-                            self.processor = Some(Err(()));
-                            return;
-                        }
-                        Ok(processor) => {
-                            self.processor = Some(Ok(processor));
-                            self.processor.as_ref().unwrap().as_ref().unwrap()
-                        }
-                    }
-                }
-                (Err(_), _) => {
-                    eprintln!("WebVfx: invalid html_path `{:?}'", self.html_path);
-                    self.processor = Some(Err(()));
+            Some(Ok(ref mut processor)) => processor,
+            Some(Err((ref error, _))) => {
+                let error = error.clone();
+                self.output_error(&error, inframes, outframe);
+                return;
+            }
+            None => match self.create_processor() {
+                Err(e) => {
+                    log::error!("failed to create renderer: {e:?}");
+                    let error = format!("failed to create renderer: {e:#}");
+                    self.status = c"failed".to_owned();
+                    self.last_error = to_cstring(&error);
+                    self.output_error(&error, inframes, outframe);
+                    let mut watcher = FileWatcher::new();
+                    self.watch_sources(&mut watcher);
+                    self.processor = Some(Err((error, watcher)));
                     return;
                 }
-                (_, Err(_)) => {
-                    eprintln!("WebVfx: invalid json_path `{:?}'", self.json_path);
-                    self.processor = Some(Err(()));
-                    return;
+                Ok(processor) => {
+                    self.set_document_info(processor.document_info());
+                    log::debug!("created renderer: {}", self.status.to_string_lossy());
+                    self.last_error = c"".to_owned();
+                    self.processor = Some(Ok(processor));
+                    self.processor.as_mut().unwrap().as_mut().unwrap()
                 }
             },
         };
 
-        if let Err(e) = processor.update(time, inframes, outframe) {
-            eprintln!("WebVfx: failed to render frame: {e:?}");
+        match processor.update(
+            time,
+            &self.params,
+            &self.time_params,
+            self.post_chain.as_ref(),
+            inframes,
+            outframe,
+        ) {
+            Ok(result) => {
+                if let Some(document_info) = &result.reloaded {
+                    self.set_document_info(document_info);
+                }
+                if let Some(error) = result.errors.last() {
+                    self.last_error = to_cstring(error);
+                }
+            }
+            Err(e) => {
+                log::error!("failed to render frame: {e:?}");
+                let error = format!("failed to render frame: {e:#}");
+                self.last_error = to_cstring(&error);
+                self.output_error(&error, inframes, outframe);
+            }
+        }
+    }
+
+    fn set_document_info(&mut self, document_info: &DocumentInfo) {
+        self.param_declarations =
+            to_cstring(&param_declarations_json(&document_info.param_declarations));
+        self.param_defaults =
+            ParamValues::default().resolve_slots(&document_info.param_declarations);
+        let mut status = String::from("ready");
+        for (i, found) in document_info.video_inputs.iter().enumerate() {
+            let _ = write!(
+                status,
+                ", {WEBVFX_SELECTOR_PREFIX}{} {}",
+                i + 1,
+                if *found { "found" } else { "missing" }
+            );
+        }
+        self.status = to_cstring(&status);
+    }
+
+    /// Fill the output according to the `on_error` param
+    fn output_error(&mut self, error: &str, inframes: [&[u32]; S], outframe: &mut [u32]) {
+        match (self.on_error.to_bytes(), inframes.first()) {
+            (b"none", _) => {}
+            (b"passthrough", Some(inframe)) => outframe.copy_from_slice(inframe),
+            _ => {
+                if !matches!(&self.error_frame, Some((message, _)) if message == error) {
+                    let mut frame = vec![0u32; outframe.len()];
+                    render_error_frame(error, self.width, self.height, &mut frame);
+                    self.error_frame = Some((error.to_string(), frame));
+                }
+                if let Some((_, frame)) = &self.error_frame {
+                    outframe.copy_from_slice(frame);
+                }
+            }
+        }
+    }
+
+    fn set_easing(&mut self, value: &CStr) {
+        value.clone_into(&mut self.easing);
+        self.time_params.easing = match param_str(value, "easing").and_then(Easing::parse) {
+            Ok(easing) => easing,
+            Err(e) => {
+                log::warn!("{e:#}, using linear");
+                Easing::Linear
+            }
+        };
+    }
+
+    fn set_time_direction(&mut self, value: &CStr) {
+        value.clone_into(&mut self.time_direction);
+        self.time_params.time_direction =
+            match param_str(value, "time_direction").and_then(TimeDirection::parse) {
+                Ok(time_direction) => time_direction,
+                Err(e) => {
+                    log::warn!("{e:#}, using forward");
+                    TimeDirection::Forward
+                }
+            };
+    }
+
+    fn set_post(&mut self, value: &CStr) {
+        value.clone_into(&mut self.post);
+        self.post_chain = match param_str(value, "post") {
+            Ok("") => None,
+            Ok(post) => match PostChain::parse(post) {
+                Ok(post) => Some(post),
+                Err(e) => {
+                    log::warn!("invalid post '{post}': {e}, using the document's");
+                    None
+                }
+            },
+            Err(e) => {
+                log::warn!("{e:#}, using the document's");
+                None
+            }
+        };
+    }
+
+    /// Set a param the renderer is created from, the renderer is recreated if the value changed
+    fn set_renderer_param(&mut self, param: fn(&mut Self) -> &mut CString, value: &CStr) {
+        let current = param(self);
+        if current.as_c_str() != value {
+            value.clone_into(current);
+            self.processor = None;
+        }
+    }
+
+    /// Watch the files the processor is created from
+    fn watch_sources(&self, watcher: &mut FileWatcher) {
+        for path in [
+            &self.html_path,
+            &self.json_path,
+            &self.timeline_path,
+            &self.subtitle_path,
+            &self.telemetry_path,
+            &self.lut_path,
+        ] {
+            if let Ok(path) = path.to_str()
+                && !path.is_empty()
+            {
+                watcher.watch(path);
+            }
+        }
+    }
+
+    /// `html_source` takes precedence over `html_path`, and `json_data` over `json_path`
+    fn create_processor(&self) -> anyhow::Result<RenderProcessor<S>> {
+        let html_path = param_str(&self.html_path, "html_path")?;
+        let html_source = param_str(&self.html_source, "html_source")?;
+        let base_url = param_str(&self.base_url, "base_url")?;
+        let json_path = param_str(&self.json_path, "json_path")?;
+        let json_data = param_str(&self.json_data, "json_data")?;
+        let optional_path = |value: &str| (!value.is_empty()).then(|| PathBuf::from(value));
+        let resources = ResourcePaths {
+            timeline: optional_path(param_str(&self.timeline_path, "timeline_path")?),
+            subtitles: optional_path(param_str(&self.subtitle_path, "subtitle_path")?),
+            telemetry: optional_path(param_str(&self.telemetry_path, "telemetry_path")?),
+            lut: optional_path(param_str(&self.lut_path, "lut_path")?),
+        };
+        let template_data = match (json_data, json_path) {
+            ("", "") => None,
+            ("", json_path) => Some(TemplateData::Path(PathBuf::from(json_path))),
+            (json_data, _) => Some(TemplateData::Json(json_data.to_string())),
+        };
+        let animation_duration = self.animation_duration.to_str().unwrap_or("5s");
+
+        if html_source.is_empty() {
+            RenderProcessor::<S>::new(
+                html_path,
+                template_data,
+                resources,
+                animation_duration,
+                self.width,
+                self.height,
+            )
+        } else {
+            RenderProcessor::<S>::new_with_source(
+                html_source,
+                if base_url.is_empty() {
+                    None
+                } else {
+                    Some(base_url)
+                },
+                template_data,
+                resources,
+                animation_duration,
+                self.width,
+                self.height,
+            )
         }
     }
 }
 
+fn to_cstring(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).unwrap_or_default()
+}
+
+fn param_str<'a>(value: &'a CStr, name: &str) -> anyhow::Result<&'a str> {
+    value
+        .to_str()
+        .with_context(|| format!("WebVfx: invalid {name} `{value:?}'"))
+}
+
+macro_rules! double_param {
+    ($index:literal, $name:literal, $explanation:literal) => {
+        frei0r_rs2::ParamInfo::new_double(
+            $name,
+            $explanation,
+            |plugin| {
+                plugin.params.doubles[$index]
+                    .or(plugin.param_defaults.doubles[$index])
+                    .unwrap_or_default()
+            },
+            |plugin, value| plugin.params.doubles[$index] = Some(value),
+        )
+    };
+}
+
+macro_rules! color_param {
+    ($index:literal, $name:literal, $explanation:literal) => {
+        frei0r_rs2::ParamInfo::new_color(
+            $name,
+            $explanation,
+            |plugin| {
+                let [r, g, b] = plugin.params.colors[$index]
+                    .or(plugin.param_defaults.colors[$index])
+                    .unwrap_or_default();
+                frei0r_rs2::Color { r, g, b }
+            },
+            |plugin, value| plugin.params.colors[$index] = Some([value.r, value.g, value.b]),
+        )
+    };
+}
+
+macro_rules! position_param {
+    ($index:literal, $name:literal, $explanation:literal) => {
+        frei0r_rs2::ParamInfo::new_position(
+            $name,
+            $explanation,
+            |plugin| {
+                let [x, y] = plugin.params.positions[$index]
+                    .or(plugin.param_defaults.positions[$index])
+                    .unwrap_or_default();
+                frei0r_rs2::Position { x, y }
+            },
+            |plugin, value| plugin.params.positions[$index] = Some([value.x, value.y]),
+        )
+    };
+}
+
+macro_rules! bool_param {
+    ($index:literal, $name:literal, $explanation:literal) => {
+        frei0r_rs2::ParamInfo::new_bool(
+            $name,
+            $explanation,
+            |plugin| {
+                plugin.params.bools[$index]
+                    .or(plugin.param_defaults.bools[$index])
+                    .unwrap_or_default()
+            },
+            |plugin, value| plugin.params.bools[$index] = Some(value),
+        )
+    };
+}
+
 trait PluginInfo {
     const NAME: &'static CStr;
     const EXPLANATION: &'static CStr;
 }
 
-impl<K, const S: usize> frei0r_rs2::Plugin for WebVfxPlugin<K, S>
-where
-    K: frei0r_rs2::PluginKind + PluginInfo + Send + 'static,
-{
-    type Kind = K;
-
-    const PARAMS: &'static [frei0r_rs2::ParamInfo<Self>] = &[
-        frei0r_rs2::ParamInfo::new_string(
-            c"html_path",
-            c"Web page file path",
-            |plugin| plugin.html_path.as_c_str(),
-            |plugin, value| value.clone_into(&mut plugin.html_path),
-        ),
-        frei0r_rs2::ParamInfo::new_string(
-            c"json_path",
-            c"JSON file, if specified then html_path is rendered as a template",
-            |plugin| plugin.json_path.as_c_str(),
-            |plugin, value| value.clone_into(&mut plugin.json_path),
-        ),
-        frei0r_rs2::ParamInfo::new_string(
-            c"duration",
-            c"CSS animation duration (specify with s or ms suffix). Sets --webvfx-animation-duration CSS property. Default 5s.",
-            |plugin| plugin.animation_duration.as_c_str(),
-            |plugin, value| value.clone_into(&mut plugin.animation_duration),
-        ),
-    ];
-
-    fn info() -> frei0r_rs2::PluginInfo {
-        frei0r_rs2::PluginInfo {
-            name: K::NAME,
-            author: c"Andrew Wason",
-            color_model: frei0r_rs2::ColorModel::RGBA8888,
-            major_version: env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap(),
-            minor_version: env!("CARGO_PKG_VERSION_MINOR").parse().unwrap(),
-            explanation: Some(K::EXPLANATION),
-        }
-    }
+/// Implement `frei0r_rs2::Plugin` with the params common to all plugin kinds,
+/// and any kind specific params.
+/// Hosts address params by index, so existing params never move: kind specific params
+/// follow `frame_rate`, and new params are appended to the end of the list.
+/// Indices used by tests are named in `param_index`.
+macro_rules! impl_plugin {
+    ($plugin:ty, $kind:ty $(, $param:expr)* $(,)?) => {
+        impl frei0r_rs2::Plugin for $plugin {
+            type Kind = $kind;
 
-    fn new(width: usize, height: usize) -> Self {
-        #[cfg(feature = "tracing")]
-        tracing_subscriber::fmt::init();
+            const PARAMS: &'static [frei0r_rs2::ParamInfo<Self>] = &[
+                frei0r_rs2::ParamInfo::new_string(
+                    c"html_path",
+                    c"Web page file path",
+                    |plugin| plugin.html_path.as_c_str(),
+                    |plugin, value| plugin.set_renderer_param(|p| &mut p.html_path, value),
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"json_path",
+                    c"JSON file, if specified then html_path is rendered as a template",
+                    |plugin| plugin.json_path.as_c_str(),
+                    |plugin, value| plugin.set_renderer_param(|p| &mut p.json_path, value),
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"duration",
+                    c"CSS animation duration (specify with s or ms suffix). Sets --webvfx-animation-duration CSS property. Default 5s.",
+                    |plugin| plugin.animation_duration.as_c_str(),
+                    |plugin, value| plugin.set_renderer_param(|p| &mut p.animation_duration, value),
+                ),
+                double_param!(0, c"param1", c"Generic double, sets --webvfx-param1 CSS property"),
+                double_param!(1, c"param2", c"Generic double, sets --webvfx-param2 CSS property"),
+                double_param!(2, c"param3", c"Generic double, sets --webvfx-param3 CSS property"),
+                double_param!(3, c"param4", c"Generic double, sets --webvfx-param4 CSS property"),
+                double_param!(4, c"param5", c"Generic double, sets --webvfx-param5 CSS property"),
+                double_param!(5, c"param6", c"Generic double, sets --webvfx-param6 CSS property"),
+                double_param!(6, c"param7", c"Generic double, sets --webvfx-param7 CSS property"),
+                double_param!(7, c"param8", c"Generic double, sets --webvfx-param8 CSS property"),
+                color_param!(0, c"color1", c"Generic color, sets --webvfx-color1 CSS property"),
+                color_param!(1, c"color2", c"Generic color, sets --webvfx-color2 CSS property"),
+                color_param!(2, c"color3", c"Generic color, sets --webvfx-color3 CSS property"),
+                color_param!(3, c"color4", c"Generic color, sets --webvfx-color4 CSS property"),
+                position_param!(0, c"position1", c"Generic position, sets --webvfx-position1-x and --webvfx-position1-y CSS properties"),
+                position_param!(1, c"position2", c"Generic position, sets --webvfx-position2-x and --webvfx-position2-y CSS properties"),
+                position_param!(2, c"position3", c"Generic position, sets --webvfx-position3-x and --webvfx-position3-y CSS properties"),
+                position_param!(3, c"position4", c"Generic position, sets --webvfx-position4-x and --webvfx-position4-y CSS properties"),
+                bool_param!(0, c"bool1", c"Generic boolean, sets --webvfx-bool1 CSS property to 0 or 1"),
+                bool_param!(1, c"bool2", c"Generic boolean, sets --webvfx-bool2 CSS property to 0 or 1"),
+                bool_param!(2, c"bool3", c"Generic boolean, sets --webvfx-bool3 CSS property to 0 or 1"),
+                bool_param!(3, c"bool4", c"Generic boolean, sets --webvfx-bool4 CSS property to 0 or 1"),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"param_declarations",
+                    c"Read only. JSON array of the params declared by the effect with <meta name=\"webvfx-param\">, available once the first frame is rendered",
+                    |plugin| plugin.param_declarations.as_c_str(),
+                    |_plugin, _value| {},
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"html_source",
+                    c"Web page HTML markup, used instead of html_path if specified",
+                    |plugin| plugin.html_source.as_c_str(),
+                    |plugin, value| plugin.set_renderer_param(|p| &mut p.html_source, value),
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"base_url",
+                    c"URL or directory path that relative URLs in html_source are resolved against. Default is the current directory.",
+                    |plugin| plugin.base_url.as_c_str(),
+                    |plugin, value| plugin.set_renderer_param(|p| &mut p.base_url, value),
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"json_data",
+                    c"JSON text, if specified then the HTML is rendered as a template. Takes precedence over json_path.",
+                    |plugin| plugin.json_data.as_c_str(),
+                    |plugin, value| plugin.set_renderer_param(|p| &mut p.json_data, value),
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"on_error",
+                    c"Output when the effect fails: error_frame renders the error text (default), passthrough copies the first input frame (error_frame for sources), none leaves the output untouched",
+                    |plugin| plugin.on_error.as_c_str(),
+                    |plugin, value| value.clone_into(&mut plugin.on_error),
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"status",
+                    c"Read only. Whether the renderer was created, and which img.webvfx-videoN input elements were found",
+                    |plugin| plugin.status.as_c_str(),
+                    |_plugin, _value| {},
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"last_error",
+                    c"Read only. The most recent template, fetch or render error",
+                    |plugin| plugin.last_error.as_c_str(),
+                    |_plugin, _value| {},
+                ),
+                frei0r_rs2::ParamInfo::new_double(
+                    c"clip_duration",
+                    c"Clip duration in seconds, --webvfx-progress goes from 0 to 1 over it. Default 0 uses the duration param",
+                    |plugin| plugin.time_params.clip_duration,
+                    |plugin, value| plugin.time_params.clip_duration = value,
+                ),
+                frei0r_rs2::ParamInfo::new_double(
+                    c"frame_rate",
+                    c"Frames per second used to compute --webvfx-frame. Default 25",
+                    |plugin| plugin.time_params.frame_rate,
+                    |plugin, value| plugin.time_params.frame_rate = value,
+                ),
+                // Kind specific params keep the indices they were added at
+                $($param,)*
+                frei0r_rs2::ParamInfo::new_double(
+                    c"time_offset",
+                    c"Document time in seconds at the start of the clip",
+                    |plugin| plugin.time_params.time_offset,
+                    |plugin, value| plugin.time_params.time_offset = value,
+                ),
+                frei0r_rs2::ParamInfo::new_double(
+                    c"time_speed",
+                    c"Playback speed multiplier. Default 1",
+                    |plugin| plugin.time_params.time_speed,
+                    |plugin, value| plugin.time_params.time_speed = value,
+                ),
+                frei0r_rs2::ParamInfo::new_double(
+                    c"loop_duration",
+                    c"If greater than 0, document time loops over this many seconds",
+                    |plugin| plugin.time_params.loop_duration,
+                    |plugin, value| plugin.time_params.loop_duration = value,
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"time_direction",
+                    c"forward (default), reverse or ping-pong. Reverse and ping-pong play over loop_duration, or clip_duration if not looping",
+                    |plugin| plugin.time_direction.as_c_str(),
+                    |plugin, value| plugin.set_time_direction(value),
+                ),
+                frei0r_rs2::ParamInfo::new_double(
+                    c"in_duration",
+                    c"Intro duration in seconds, sets --webvfx-in-duration, --webvfx-in-delay and --webvfx-in-progress CSS properties",
+                    |plugin| plugin.time_params.in_duration,
+                    |plugin, value| plugin.time_params.in_duration = value,
+                ),
+                frei0r_rs2::ParamInfo::new_double(
+                    c"out_duration",
+                    c"Outro duration in seconds, sets --webvfx-out-duration, --webvfx-out-delay and --webvfx-out-progress CSS properties so the outro ends at clip_duration",
+                    |plugin| plugin.time_params.out_duration,
+                    |plugin, value| plugin.time_params.out_duration = value,
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"timeline_path",
+                    c"Keyframe timeline JSON file, overrides a timeline linked from the HTML with <link rel=\"webvfx-timeline\">",
+                    |plugin| plugin.timeline_path.as_c_str(),
+                    |plugin, value| plugin.set_renderer_param(|p| &mut p.timeline_path, value),
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"subtitle_path",
+                    c"SRT or WebVTT subtitles shown in .webvfx-subtitle elements, overrides their data-webvfx-subtitles attribute",
+                    |plugin| plugin.subtitle_path.as_c_str(),
+                    |plugin, value| plugin.set_renderer_param(|p| &mut p.subtitle_path, value),
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"telemetry_path",
+                    c"CSV or GPX telemetry file, overrides telemetry linked from the HTML with <link rel=\"webvfx-telemetry\">",
+                    |plugin| plugin.telemetry_path.as_c_str(),
+                    |plugin, value| plugin.set_renderer_param(|p| &mut p.telemetry_path, value),
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"post",
+                    c"Post-processing applied to the output, e.g. vignette(0.4) grain(0.05). Operations are grain, vignette, blur and sharpen. Overrides <meta name=\"webvfx-post\">",
+                    |plugin| plugin.post.as_c_str(),
+                    |plugin, value| plugin.set_post(value),
+                ),
+                frei0r_rs2::ParamInfo::new_string(
+                    c"lut_path",
+                    c"3D LUT .cube file applied to the output, overrides a LUT linked from the HTML with <link rel=\"webvfx-lut\">",
+                    |plugin| plugin.lut_path.as_c_str(),
+                    |plugin, value| plugin.set_renderer_param(|p| &mut p.lut_path, value),
+                ),
+                frei0r_rs2::ParamInfo::new_double(
+                    c"telemetry_offset",
+                    c"Seconds added to document time when looking up telemetry, e.g. 30 if the clip starts 30s into the recording",
+                    |plugin| plugin.time_params.telemetry_offset,
+                    |plugin, value| plugin.time_params.telemetry_offset = value,
+                ),
+            ];
 
-        WebVfxPlugin::new(width as u32, height as u32)
-    }
+            fn info() -> frei0r_rs2::PluginInfo {
+                frei0r_rs2::PluginInfo {
+                    name: <$kind as PluginInfo>::NAME,
+                    author: c"Andrew Wason",
+                    color_model: frei0r_rs2::ColorModel::RGBA8888,
+                    major_version: env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap(),
+                    minor_version: env!("CARGO_PKG_VERSION_MINOR").parse().unwrap(),
+                    explanation: Some(<$kind as PluginInfo>::EXPLANATION),
+                }
+            }
+
+            fn new(width: usize, height: usize) -> Self {
+                crate::logging::init_logging();
+
+                WebVfxPlugin::new(width as u32, height as u32)
+            }
+        }
+    };
 }
+
+impl_plugin!(filter::FilterPlugin, frei0r_rs2::KindFilter);
+impl_plugin!(
+    mixer2::Mixer2Plugin,
+    frei0r_rs2::KindMixer2,
+    frei0r_rs2::ParamInfo::new_double(
+        c"position",
+        c"Transition position from 0 to 1, sets the eased --webvfx-transition CSS property",
+        |plugin| plugin.time_params.transition.unwrap_or_default(),
+        |plugin, value| plugin.time_params.transition = Some(value),
+    ),
+    frei0r_rs2::ParamInfo::new_string(
+        c"easing",
+        c"Easing applied to position: linear (default), ease, ease-in, ease-out, ease-in-out or cubic-bezier(x1, y1, x2, y2)",
+        |plugin| plugin.easing.as_c_str(),
+        |plugin, value| plugin.set_easing(value),
+    ),
+    frei0r_rs2::ParamInfo::new_bool(
+        c"scrub_animations",
+        c"Drive CSS animations from the eased position over the duration param instead of time",
+        |plugin| plugin.time_params.scrub_animations,
+        |plugin, value| plugin.time_params.scrub_animations = value,
+    ),
+);
+impl_plugin!(mixer3::Mixer3Plugin, frei0r_rs2::KindMixer3);
+impl_plugin!(source::SourcePlugin, frei0r_rs2::KindSource);
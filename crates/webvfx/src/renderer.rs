@@ -1,11 +1,11 @@
 // Copyright (C) 2025 Andrew Wason
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::sync::Arc;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use anyrender::{ImageRenderer, PaintScene};
 use blitz_dom::{
-    DocumentConfig,
+    DocumentConfig, LocalName, local_name,
     node::{ImageData, RasterImageData, SpecialElementData},
 };
 use blitz_html::HtmlDocument;
@@ -17,8 +17,50 @@ use blitz_traits::{
 use linebender_resource_handle::Blob;
 use smallvec::SmallVec;
 
+use behaviors::TextBehaviors;
+use fit::FitText;
+use lut::{Interpolation, Lut3d, WEBVFX_LUT_INTERPOLATION_ATTRIBUTE, WEBVFX_LUT_LINK_SELECTOR};
+use params::{ParamDeclaration, ParamValues};
+use particles::Particles;
+use post::PostChain;
+use preprocess::Preprocess;
+use scroll::ScrollBehaviors;
+use style::InlineStyles;
+use subtitles::{
+    SubtitlePlayer, Subtitles, WEBVFX_CUE_BOLD_CLASS, WEBVFX_CUE_ITALIC_CLASS,
+    WEBVFX_CUE_UNDERLINE_CLASS,
+};
+use telemetry::{
+    Telemetry, TelemetryPlayer, WEBVFX_ROUTE_MARKER_CLASS, WEBVFX_ROUTE_PATH_CLASS,
+    WEBVFX_ROUTE_SELECTOR, WEBVFX_TELEMETRY_LINK_SELECTOR,
+};
+use timeline::{Timeline, TimelinePlayer, WEBVFX_TIMELINE_LINK_SELECTOR};
+use timing::{
+    TimeParams, WEBVFX_CSS_IN_DELAY_PROPERTY, WEBVFX_CSS_IN_DURATION_PROPERTY,
+    WEBVFX_CSS_OUT_DELAY_PROPERTY, WEBVFX_CSS_OUT_DURATION_PROPERTY, parse_css_duration,
+};
+
+pub mod behaviors;
+pub mod easing;
+pub mod error;
+pub mod fit;
+pub mod imaging;
+pub mod lut;
 pub mod net;
+pub mod params;
+pub mod particles;
+pub mod post;
+pub mod preprocess;
 pub mod processor;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod scroll;
+mod style;
+pub mod subtitles;
+pub mod telemetry;
+pub mod timeline;
+pub mod timing;
+pub mod watch;
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "anyrender_vello_cpu")] {
@@ -32,36 +74,157 @@ cfg_if::cfg_if! {
     }
 }
 
-// Node ID mapped to a pair of video frame buffers
-type VideoNode = (SmallVec<[usize; 32]>, [Arc<Vec<u8>>; 2]);
+// Node ID mapped to a pair of video frame buffers, and the processing of input frames
+type VideoNode = (SmallVec<[usize; 32]>, [Arc<Vec<u8>>; 2], Option<Preprocess>);
 
 pub const WEBVFX_SELECTOR_PREFIX: &str = "img.webvfx-video";
 pub const WEBVFX_CSS_ANIMATION_PROPERTY: &str = "--webvfx-animation-duration";
 
+/// Forward steps in host time longer than this many seconds are treated as seeks.
+/// Fixed rather than a number of frames, so low frame rates, dropped frames and
+/// previews rendering every Nth frame play without reloading.
+const MAX_TIME_STEP: f64 = 1.0;
+
 struct WebVfxRenderer<const S: usize> {
     width: u32,
     height: u32,
+    base_url: Url,
+    html: String,
+    ua_stylesheet: String,
     document: HtmlDocument,
     renderer: AnyRender,
     video_nodes: [Option<VideoNode>; S],
     video_node_index: usize,
+    param_declarations: Vec<ParamDeclaration>,
+    /// Parsed `animation_duration` in seconds
+    animation_duration: Option<f64>,
+    /// Host time of the last frame rendered
+    last_time: Option<f64>,
+    inline_styles: InlineStyles,
+    timeline: Option<TimelinePlayer>,
+    subtitles: SubtitlePlayer,
+    telemetry: Option<TelemetryPlayer>,
+    behaviors: TextBehaviors,
+    scroll: ScrollBehaviors,
+    fit: FitText,
+    particles: Particles,
+    /// Post-processing declared by the document
+    post: Option<PostChain>,
+    /// LUT applied to the output
+    lut: Option<Lut3d>,
+    lut_interpolation: Interpolation,
+    /// LUTs of the inputs by URL, `None` if loading failed
+    input_luts: HashMap<String, Option<Arc<Lut3d>>>,
+    #[cfg(feature = "scripting")]
+    script: Option<script::Script>,
+    net_provider: Arc<net::SyncNetProvider>,
+    /// Errors other than fetch errors since the last `take_errors`
+    errors: Vec<String>,
 }
 
 impl<const S: usize> WebVfxRenderer<S> {
     fn new(base_url: &Url, html: &str, animation_duration: &str, width: u32, height: u32) -> Self {
-        let css_properties = format!(
+        let ua_stylesheet = format!(
             r"
             :root {{
-                {WEBVFX_CSS_ANIMATION_PROPERTY}: {animation_duration}
+                {WEBVFX_CSS_ANIMATION_PROPERTY}: {animation_duration};
+                {WEBVFX_CSS_IN_DURATION_PROPERTY}: 0s;
+                {WEBVFX_CSS_IN_DELAY_PROPERTY}: 0s;
+                {WEBVFX_CSS_OUT_DURATION_PROPERTY}: 0s;
+                {WEBVFX_CSS_OUT_DELAY_PROPERTY}: 0s;
+            }}
+            .{WEBVFX_CUE_BOLD_CLASS} {{ font-weight: bold; }}
+            .{WEBVFX_CUE_ITALIC_CLASS} {{ font-style: italic; }}
+            .{WEBVFX_CUE_UNDERLINE_CLASS} {{ text-decoration: underline; }}
+            {WEBVFX_ROUTE_SELECTOR} {{ position: relative; }}
+            .{WEBVFX_ROUTE_PATH_CLASS} {{ display: block; width: 100%; height: 100%; }}
+            .{WEBVFX_ROUTE_MARKER_CLASS} {{
+                position: absolute;
+                width: 16px;
+                height: 16px;
+                border-radius: 50%;
+                background-color: red;
+                transform: translate(-50%, -50%);
             }}
         "
         );
+        let net_provider = Arc::new(net::SyncNetProvider::with_cache());
+        let (document, video_nodes) =
+            Self::load_document(base_url, html, &ua_stylesheet, &net_provider, width, height);
+        let param_declarations = ParamDeclaration::from_document(&document);
+        let behaviors = TextBehaviors::new(&document);
+        let scroll = ScrollBehaviors::new(&document);
+        let fit = FitText::new(&document);
+        let particles = Particles::new(&document);
+
+        let renderer = AnyRender::new(width, height);
+        let mut renderer = Self {
+            width,
+            height,
+            base_url: base_url.clone(),
+            html: html.to_string(),
+            ua_stylesheet,
+            document,
+            renderer,
+            video_nodes,
+            video_node_index: 0, // We populated special_data with the 0th image buffer
+            param_declarations,
+            animation_duration: parse_css_duration(animation_duration),
+            last_time: None,
+            inline_styles: InlineStyles::default(),
+            timeline: None,
+            subtitles: SubtitlePlayer::default(),
+            telemetry: None,
+            behaviors,
+            scroll,
+            fit,
+            particles,
+            post: None,
+            lut: None,
+            lut_interpolation: Interpolation::default(),
+            input_luts: HashMap::new(),
+            #[cfg(feature = "scripting")]
+            script: None,
+            net_provider,
+            errors: Vec::new(),
+        };
+        renderer.load_linked_resources();
+        renderer.bind_input_luts();
+        match PostChain::from_document(&renderer.document) {
+            Some(Ok(post)) => renderer.post = Some(post),
+            Some(Err(e)) => {
+                log::warn!("invalid post-processing: {e}");
+                renderer
+                    .errors
+                    .push(format!("invalid post-processing: {e}"));
+            }
+            None => {}
+        }
+        #[cfg(feature = "scripting")]
+        {
+            renderer.script =
+                script::Script::from_document(&renderer.document, &mut renderer.errors);
+        }
+        renderer
+    }
+
+    /// Parse the document and attach frame buffers to the video elements.
+    /// The document is resolved at time 0 so animations always start at 0,
+    /// regardless of the time of the first frame rendered.
+    fn load_document(
+        base_url: &Url,
+        html: &str,
+        ua_stylesheet: &str,
+        net_provider: &Arc<net::SyncNetProvider>,
+        width: u32,
+        height: u32,
+    ) -> (HtmlDocument, [Option<VideoNode>; S]) {
         let mut document = HtmlDocument::from_html(
             html,
             DocumentConfig {
                 base_url: Some(base_url.as_str().into()),
-                ua_stylesheets: Some(vec![css_properties]),
-                net_provider: Some(Arc::new(net::SyncNetProvider::new())),
+                ua_stylesheets: Some(vec![ua_stylesheet.to_string()]),
+                net_provider: Some(net_provider.clone()),
                 viewport: Some(Viewport::new(width, height, 1.0, ColorScheme::Light)),
                 ..Default::default()
             },
@@ -72,19 +235,36 @@ impl<const S: usize> WebVfxRenderer<S> {
                     document.query_selector_all(&format!("{}{}", WEBVFX_SELECTOR_PREFIX, i + 1))
                     && !node_ids.is_empty()
                 {
-                    let frame = vec![0u8; (width * height * 4) as usize];
+                    let preprocess = document.get_node(node_ids[0]).and_then(|node| {
+                        Preprocess::from_node(node, width, height)
+                            .inspect_err(|e| {
+                                log::warn!(
+                                    "invalid preprocessing of {WEBVFX_SELECTOR_PREFIX}{}: {e}",
+                                    i + 1
+                                );
+                            })
+                            .ok()
+                            .flatten()
+                    });
+                    let (frame_width, frame_height) = preprocess
+                        .as_ref()
+                        .map_or((width, height), Preprocess::output_size);
+                    let frame = vec![0u8; (frame_width * frame_height * 4) as usize];
                     let frame_arc = Arc::new(frame.clone());
                     node_ids.iter().copied().for_each(|node_id| {
                         if let Some(node) = document.get_node_mut(node_id)
                             && let Some(element_data) = node.element_data_mut()
                         {
-                            element_data.special_data =
-                                SpecialElementData::Image(Box::new(ImageData::Raster(
-                                    RasterImageData::new(width, height, frame_arc.clone()),
-                                )));
+                            element_data.special_data = SpecialElementData::Image(Box::new(
+                                ImageData::Raster(RasterImageData::new(
+                                    frame_width,
+                                    frame_height,
+                                    frame_arc.clone(),
+                                )),
+                            ));
                         }
                     });
-                    Some((node_ids, [frame_arc, Arc::new(frame)]))
+                    Some((node_ids, [frame_arc, Arc::new(frame)], preprocess))
                 } else {
                     None
                 }
@@ -92,19 +272,228 @@ impl<const S: usize> WebVfxRenderer<S> {
             .collect::<Vec<Option<VideoNode>>>()
             .try_into()
             .unwrap();
+        document.resolve(0.0);
+        (document, video_nodes)
+    }
 
-        let renderer = AnyRender::new(width, height);
-        Self {
-            width,
-            height,
-            document,
-            renderer,
-            video_nodes,
-            video_node_index: 0, // We populated special_data with the 0th image buffer
+    /// Discard all time dependent state (running animations and transitions)
+    /// by reloading the document, so seeking renders the same frame as a new renderer.
+    /// Resources fetched by the document are not fetched again, the processor creates
+    /// a new renderer when fetched files change.
+    fn reset(&mut self) {
+        let (document, video_nodes) = Self::load_document(
+            &self.base_url,
+            &self.html,
+            &self.ua_stylesheet,
+            &self.net_provider,
+            self.width,
+            self.height,
+        );
+        self.document = document;
+        self.video_nodes = video_nodes;
+        self.video_node_index = 0;
+        self.inline_styles = InlineStyles::default();
+        if let Some(timeline) = &mut self.timeline {
+            timeline.bind(&self.document);
+        }
+        self.subtitles.bind(&self.document);
+        self.behaviors.bind(&self.document);
+        self.scroll.bind(&self.document);
+        self.fit.bind(&self.document);
+        self.particles.bind(&self.document);
+        self.bind_input_luts();
+        #[cfg(feature = "scripting")]
+        if let Some(script) = &mut self.script {
+            script.reset();
+        }
+        if let Some(telemetry) = &mut self.telemetry {
+            telemetry.bind(&mut self.document);
+            self.document.resolve(0.0);
         }
     }
 
-    fn update(&mut self, time: f64, inframes: [&[u8]; S], outframe: &mut [u8]) {
+    /// Load the timeline linked with `<link rel="webvfx-timeline" href="...">`,
+    /// the telemetry linked with `<link rel="webvfx-telemetry" href="...">`,
+    /// the output LUT linked with `<link rel="webvfx-lut" href="...">`
+    /// and the subtitles of `.webvfx-subtitle` elements with a `data-webvfx-subtitles` attribute
+    fn load_linked_resources(&mut self) {
+        if let Some(href) = self.link_href(WEBVFX_TIMELINE_LINK_SELECTOR)
+            && let Some(timeline) = self.load_linked(&href, "timeline", Timeline::parse)
+        {
+            self.set_timeline(timeline);
+        }
+        if let Some(href) = self.link_href(WEBVFX_TELEMETRY_LINK_SELECTOR)
+            && let Some(telemetry) = self.load_linked(&href, "telemetry", Telemetry::parse)
+        {
+            self.set_telemetry(telemetry);
+        }
+        if let Some(href) = self.link_href(WEBVFX_LUT_LINK_SELECTOR) {
+            self.lut = self.load_linked(&href, "LUT", Lut3d::parse);
+            if let Some(interpolation) = self.link_attr(
+                WEBVFX_LUT_LINK_SELECTOR,
+                LocalName::from(WEBVFX_LUT_INTERPOLATION_ATTRIBUTE),
+            ) {
+                match Interpolation::parse(&interpolation) {
+                    Ok(interpolation) => self.lut_interpolation = interpolation,
+                    Err(e) => {
+                        log::warn!("{e}");
+                        self.errors.push(e.to_string());
+                    }
+                }
+            }
+        }
+        for source in SubtitlePlayer::linked_sources(&self.document) {
+            if let Some(subtitles) = self.load_linked(&source, "subtitles", |data| {
+                Subtitles::parse(&String::from_utf8_lossy(data))
+            }) {
+                self.subtitles.insert_linked(source, subtitles);
+            }
+        }
+        self.subtitles.bind(&self.document);
+    }
+
+    /// The `href` of the first element matching `selector`
+    fn link_href(&self, selector: &str) -> Option<String> {
+        self.link_attr(selector, local_name!("href"))
+    }
+
+    /// An attribute of the first element matching `selector`
+    fn link_attr(&self, selector: &str, name: LocalName) -> Option<String> {
+        self.document
+            .query_selector_all(selector)
+            .ok()
+            .and_then(|node_ids| node_ids.first().copied())
+            .and_then(|node_id| self.document.get_node(node_id))
+            .and_then(|node| node.attr(name))
+            .map(String::from)
+    }
+
+    /// Load the `data-webvfx-lut` LUTs of the inputs, once per URL,
+    /// and give them to the input preprocessing
+    fn bind_input_luts(&mut self) {
+        let hrefs: Vec<String> = self
+            .video_nodes
+            .iter()
+            .flatten()
+            .filter_map(|(_, _, preprocess)| preprocess.as_ref()?.lut_href().map(String::from))
+            .collect();
+        for href in hrefs {
+            if !self.input_luts.contains_key(&href) {
+                let lut = self.load_linked(&href, "LUT", Lut3d::parse).map(Arc::new);
+                self.input_luts.insert(href, lut);
+            }
+        }
+        for (_, _, preprocess) in self.video_nodes.iter_mut().flatten() {
+            if let Some(preprocess) = preprocess
+                && let Some(href) = preprocess.lut_href()
+            {
+                let lut = self.input_luts.get(href).cloned().flatten();
+                preprocess.set_lut(lut);
+            }
+        }
+    }
+
+    /// Fetch and parse a resource linked from the document, `kind` names it in errors
+    fn load_linked<T>(
+        &mut self,
+        href: &str,
+        kind: &str,
+        parse: impl FnOnce(&[u8]) -> anyhow::Result<T>,
+    ) -> Option<T> {
+        let url = match self.base_url.join(href) {
+            Ok(url) => url,
+            Err(e) => {
+                log::warn!("invalid {kind} url '{href}': {e}");
+                self.errors
+                    .push(format!("invalid {kind} url '{href}': {e}"));
+                return None;
+            }
+        };
+        // Fetch errors are recorded by the net provider
+        let bytes = self.net_provider.fetch_url(url)?;
+        match parse(&bytes) {
+            Ok(resource) => Some(resource),
+            Err(e) => {
+                log::warn!("failed to parse {kind} '{href}': {e:?}");
+                self.errors
+                    .push(format!("failed to parse {kind} '{href}': {e:#}"));
+                None
+            }
+        }
+    }
+
+    /// Subtitles for all `.webvfx-subtitle` elements, replacing those linked from the document
+    fn set_subtitles(&mut self, subtitles: Subtitles) {
+        self.subtitles.set_subtitles(subtitles);
+        self.subtitles.bind(&self.document);
+    }
+
+    /// Replace the output LUT linked from the document
+    fn set_lut(&mut self, lut: Lut3d) {
+        self.lut = Some(lut);
+    }
+
+    /// Replace the telemetry linked from the document
+    fn set_telemetry(&mut self, telemetry: Telemetry) {
+        if let Some(mut previous) = self.telemetry.take() {
+            previous.unbind(&mut self.document);
+        }
+        self.telemetry = Some(TelemetryPlayer::new(telemetry, &mut self.document));
+        // Lay out the generated route elements, markers are positioned using the layout
+        self.document.resolve(0.0);
+    }
+
+    /// Template data exposed to scripts
+    #[cfg(feature = "scripting")]
+    fn set_script_data(&mut self, data: &serde_json::Value) {
+        if let Some(script) = &mut self.script {
+            script.set_data(data);
+        }
+    }
+
+    /// Replace the timeline linked from the document
+    fn set_timeline(&mut self, timeline: Timeline) {
+        self.timeline = Some(TimelinePlayer::new(timeline, &self.document));
+    }
+
+    fn fetched_files(&self) -> Vec<PathBuf> {
+        self.net_provider.fetched_files()
+    }
+
+    fn take_errors(&mut self) -> Vec<String> {
+        let mut errors = std::mem::take(&mut self.errors);
+        errors.extend(self.net_provider.take_errors());
+        errors
+    }
+
+    /// Whether an element was found for each input video
+    fn video_inputs(&self) -> Vec<bool> {
+        self.video_nodes.iter().map(Option::is_some).collect()
+    }
+
+    fn param_declarations(&self) -> &[ParamDeclaration] {
+        &self.param_declarations
+    }
+
+    /// `post` replaces any post-processing declared by the document
+    fn update(
+        &mut self,
+        time: f64,
+        params: &ParamValues,
+        time_params: &TimeParams,
+        post: Option<&PostChain>,
+        inframes: [&[u8]; S],
+        outframe: &mut [u8],
+    ) {
+        // Seeks are detected from host time, document time can run backwards while playing
+        if let Some(last_time) = self.last_time
+            && (time < last_time || time - last_time > MAX_TIME_STEP)
+        {
+            self.reset();
+        }
+        self.last_time = Some(time);
+        let document_time = time_params.document_time(time, self.animation_duration);
+
         self.video_node_index = (self.video_node_index + 1) % 2;
         self.video_nodes
             .iter_mut()
@@ -112,10 +501,12 @@ impl<const S: usize> WebVfxRenderer<S> {
             .filter_map(|(video_node, inframe)| {
                 video_node.as_mut().map(|video_node| (video_node, inframe))
             })
-            .for_each(|((video_node_ids, frames), inframe)| {
-                Arc::get_mut(&mut frames[self.video_node_index])
-                    .unwrap()
-                    .copy_from_slice(inframe);
+            .for_each(|((video_node_ids, frames, preprocess), inframe)| {
+                let frame = Arc::get_mut(&mut frames[self.video_node_index]).unwrap();
+                match preprocess {
+                    Some(preprocess) => preprocess.apply(inframe, frame),
+                    None => frame.copy_from_slice(inframe),
+                }
                 video_node_ids.iter().copied().for_each(|node_id| {
                     // Safe to unwrap since we verified all this when contructing
                     let raster_data = self
@@ -129,14 +520,69 @@ impl<const S: usize> WebVfxRenderer<S> {
                     raster_data.data = Blob::new(frames[self.video_node_index].clone());
                 });
             });
-        self.document.resolve(time);
+        let root_id = self.document.root_element().id;
+        self.inline_styles
+            .push(root_id, &params.css_declarations(&self.param_declarations));
+        self.inline_styles.push(
+            root_id,
+            &time_params.css_declarations(time, self.animation_duration),
+        );
+        if let Some(timeline) = &mut self.timeline {
+            timeline.apply(document_time, &mut self.document, &mut self.inline_styles);
+        }
+        self.subtitles.apply(document_time, &mut self.document);
+        if let Some(telemetry) = &mut self.telemetry {
+            telemetry.apply(
+                document_time + time_params.telemetry_offset,
+                &mut self.document,
+                &mut self.inline_styles,
+            );
+        }
+        let clip_duration = time_params.clip_duration(self.animation_duration);
+        self.behaviors.apply(
+            document_time,
+            time_params.frame_rate,
+            clip_duration,
+            &mut self.document,
+        );
+        self.scroll.apply(
+            document_time,
+            clip_duration,
+            &self.document,
+            &mut self.inline_styles,
+        );
+        #[cfg(feature = "scripting")]
+        if let Some(script) = &mut self.script
+            && let Some(error) = script.run(
+                document_time,
+                params,
+                &self.param_declarations,
+                &mut self.document,
+                &mut self.inline_styles,
+            )
+        {
+            self.errors.push(error);
+        }
+        self.fit
+            .push_styles(&self.document, &mut self.inline_styles);
+        self.inline_styles.apply(&mut self.document);
+        self.document.resolve(document_time);
+        self.fit
+            .fit(document_time, &mut self.document, &mut self.inline_styles);
         self.renderer.render(
             |scene| {
                 scene.reset();
                 paint_scene(scene, &self.document, 1.0, self.width, self.height);
+                self.particles.paint(document_time, &self.document, scene);
             },
             outframe,
         );
+        if let Some(lut) = &self.lut {
+            lut.apply(outframe, self.lut_interpolation);
+        }
+        if let Some(post) = post.or(self.post.as_ref()) {
+            post.apply(time, outframe, self.width, self.height);
+        }
     }
 }
 
@@ -144,7 +590,7 @@ impl<const S: usize> WebVfxRenderer<S> {
 mod tests {
     use std::path::Path;
 
-    use crate::process_template;
+    use crate::{TemplateData, process_template, process_template_source};
 
     use super::*;
     use test_support::{HEIGHT, RgbaImage, WIDTH, assert_reference, read_image, testdata};
@@ -153,9 +599,10 @@ mod tests {
         html_file: &str,
         json_file: Option<&str>,
     ) -> (WebVfxRenderer<S>, RgbaImage) {
+        let json_path = json_file.map(|f| testdata!().join(f));
         let (url, html) = process_template(
             testdata!().join(html_file),
-            json_file.map(|f| testdata!().join(f)),
+            json_path.map(TemplateData::Path).as_ref(),
         )
         .unwrap();
         let renderer = WebVfxRenderer::<S>::new(&url, &html, "5s", WIDTH, HEIGHT);
@@ -179,12 +626,236 @@ mod tests {
             .unwrap();
         renderer.update(
             time,
+            &ParamValues::default(),
+            &TimeParams::default(),
+            None,
             inframe_refs,
             output.as_flat_samples_mut().image_mut_slice().unwrap(),
         );
         assert_reference(reference_file, output);
     }
 
+    fn render_frame(renderer: &mut WebVfxRenderer<0>, time: f64) -> RgbaImage {
+        let mut output = RgbaImage::new(WIDTH, HEIGHT);
+        renderer.update(
+            time,
+            &ParamValues::default(),
+            &TimeParams::default(),
+            None,
+            [],
+            output.as_flat_samples_mut().image_mut_slice().unwrap(),
+        );
+        output
+    }
+
+    fn assert_seek(times: &[f64]) {
+        let (mut fresh, _) = init_renderer::<0>("animation.html", None);
+        let (mut r, _) = init_renderer::<0>("animation.html", None);
+        let mut output = None;
+        for time in times {
+            output = Some(render_frame(&mut r, *time));
+        }
+        let expected = render_frame(&mut fresh, *times.last().unwrap());
+        assert!(
+            output.unwrap() == expected,
+            "render after times {times:?} differs from a new renderer"
+        );
+    }
+
+    #[test]
+    fn test_seek_backwards() {
+        assert_seek(&[3.0, 2.0]);
+        assert_seek(&[3.0, 0.0, 2.0]);
+        assert_seek(&[2.5, 2.25, 2.0]);
+    }
+
+    #[test]
+    fn test_seek_forwards() {
+        assert_seek(&[0.0, 2.0]);
+        assert_seek(&[0.0, 5.0, 2.0]);
+    }
+
+    #[test]
+    fn test_sequential() {
+        assert_seek(&[0.0, 0.5, 1.0, 1.5, 2.0]);
+    }
+
+    #[test]
+    fn test_reverse_no_reload() {
+        let (mut r, mut output) = init_renderer::<0>("animation.html", None);
+        let time_params = TimeParams {
+            time_direction: timing::TimeDirection::Reverse,
+            ..Default::default()
+        };
+        let mut render_at = |r: &mut WebVfxRenderer<0>, time: f64| {
+            r.update(
+                time,
+                &ParamValues::default(),
+                &time_params,
+                None,
+                [],
+                output.as_flat_samples_mut().image_mut_slice().unwrap(),
+            );
+        };
+        let marker = LocalName::from("data-test-marker");
+        render_at(&mut r, 0.0);
+        let root_id = r.document.root_element().id;
+        r.document.mutate().set_attribute(
+            root_id,
+            blitz_dom::QualName::new(None, blitz_dom::ns!(), marker.clone()),
+            "1",
+        );
+        // Consecutive frames run document time backwards without reloading the document
+        render_at(&mut r, 0.04);
+        render_at(&mut r, 0.08);
+        assert!(r.document.root_element().attr(marker.clone()).is_some());
+        // Seeking reloads it
+        render_at(&mut r, 0.0);
+        assert!(r.document.root_element().attr(marker).is_none());
+    }
+
+    #[test]
+    fn test_low_frame_rate_no_reload() {
+        let (mut r, _) = init_renderer::<0>("animation.html", None);
+        let marker = LocalName::from("data-test-marker");
+        render_frame(&mut r, 0.0);
+        let root_id = r.document.root_element().id;
+        r.document.mutate().set_attribute(
+            root_id,
+            blitz_dom::QualName::new(None, blitz_dom::ns!(), marker.clone()),
+            "1",
+        );
+        // 15fps host, frame rate left at the default
+        for frame in 1..=15 {
+            render_frame(&mut r, f64::from(frame) / 15.0);
+        }
+        assert!(r.document.root_element().attr(marker).is_some());
+    }
+
+    #[test]
+    fn test_subtitles() {
+        let (mut r, _) = init_renderer::<0>("subtitles.html", None);
+        let subtitle_text = |r: &WebVfxRenderer<0>| {
+            let node_id = r
+                .document
+                .query_selector_all(subtitles::WEBVFX_SUBTITLE_SELECTOR)
+                .unwrap()[0];
+            r.document.get_node(node_id).unwrap().text_content()
+        };
+        render_frame(&mut r, 0.0);
+        assert_eq!(subtitle_text(&r), "");
+        render_frame(&mut r, 1.0);
+        assert_eq!(subtitle_text(&r), "Hello world");
+        render_frame(&mut r, 2.5);
+        assert_eq!(subtitle_text(&r), "Secondline");
+        render_frame(&mut r, 1.0);
+        assert_eq!(subtitle_text(&r), "Hello world");
+    }
+
+    #[test]
+    fn test_telemetry() {
+        let (mut r, _) = init_renderer::<0>("telemetry.html", None);
+        let text = |r: &WebVfxRenderer<0>, selector: &str| {
+            let node_id = r.document.query_selector_all(selector).unwrap()[0];
+            r.document.get_node(node_id).unwrap().text_content()
+        };
+        render_frame(&mut r, 0.8);
+        assert_eq!(text(&r, "#speed"), "14.0");
+        assert_eq!(text(&r, "#altitude"), "104");
+        render_frame(&mut r, 3.0);
+        assert_eq!(text(&r, "#speed"), "20.0");
+    }
+
+    #[test]
+    fn test_telemetry_offset() {
+        let (mut r, mut output) = init_renderer::<0>("telemetry.html", None);
+        let time_params = TimeParams {
+            telemetry_offset: 0.8,
+            ..Default::default()
+        };
+        r.update(
+            0.0,
+            &ParamValues::default(),
+            &time_params,
+            None,
+            [],
+            output.as_flat_samples_mut().image_mut_slice().unwrap(),
+        );
+        let node_id = r.document.query_selector_all("#speed").unwrap()[0];
+        assert_eq!(r.document.get_node(node_id).unwrap().text_content(), "14.0");
+    }
+
+    #[test]
+    fn test_telemetry_override() {
+        let (mut r, _) = init_renderer::<0>("telemetry.html", None);
+        let markers = |r: &WebVfxRenderer<0>| {
+            r.document
+                .query_selector_all(&format!(".{WEBVFX_ROUTE_MARKER_CLASS}"))
+                .unwrap()
+                .len()
+        };
+        let track = b"time,lat,lon\n0,51.5,-0.1\n1,51.6,-0.2\n";
+        r.set_telemetry(Telemetry::parse(track).unwrap());
+        assert_eq!(markers(&r), 1);
+        r.set_telemetry(Telemetry::parse(track).unwrap());
+        assert_eq!(markers(&r), 1);
+        render_frame(&mut r, 1.0);
+        render_frame(&mut r, 0.0);
+        assert_eq!(markers(&r), 1);
+    }
+
+    #[cfg(feature = "scripting")]
+    #[test]
+    fn test_script() {
+        let (mut r, _) = init_renderer::<0>("script.html", None);
+        let title = |r: &WebVfxRenderer<0>| {
+            let node_id = r.document.query_selector_all("#title").unwrap()[0];
+            let node = r.document.get_node(node_id).unwrap();
+            (
+                node.text_content(),
+                node.attr(LocalName::from("data-start")).map(String::from),
+            )
+        };
+        render_frame(&mut r, 0.0);
+        assert_eq!(title(&r), ("Hello!".to_string(), Some("yes".to_string())));
+        // Changes from the previous frame are undone before the script runs again
+        render_frame(&mut r, 0.04);
+        assert_eq!(title(&r), ("Hello!".to_string(), None));
+        render_frame(&mut r, 0.08);
+        assert_eq!(title(&r), ("Hello!".to_string(), None));
+        // The same frame renders the same after other frames
+        let sequential = render_frame(&mut r, 0.12);
+        let (mut fresh, _) = init_renderer::<0>("script.html", None);
+        assert!(render_frame(&mut fresh, 0.12) == sequential);
+    }
+
+    #[test]
+    fn test_fit() {
+        let (mut r, _) = init_renderer::<0>("fit.html", None);
+        render_frame(&mut r, 0.0);
+        let node_id = r.document.query_selector_all("#title").unwrap()[0];
+        let node = r.document.get_node(node_id).unwrap();
+        let style = node.attr(local_name!("style")).unwrap();
+        let font_size: u32 = style
+            .split_once("font-size: ")
+            .and_then(|(_, size)| size.split_once("px"))
+            .and_then(|(size, _)| size.parse().ok())
+            .unwrap();
+        assert!((8..100).contains(&font_size), "font size {font_size}");
+        assert!(node.final_layout.content_size.width <= node.final_layout.size.width + 0.5);
+    }
+
+    #[test]
+    fn test_lut() {
+        let (mut r, _) = init_renderer::<0>("lut.html", None);
+        let output = render_frame(&mut r, 0.0);
+        // The style.css background #f4e8d2 inverted
+        let pixel = output.get_pixel(0, 0).0;
+        for (value, expected) in pixel.into_iter().zip([0x0b, 0x17, 0x2d, 0xff]) {
+            assert!(value.abs_diff(expected) <= 1, "{pixel:?}");
+        }
+    }
+
     #[test]
     fn test_source() {
         let (mut r, mut output) = init_renderer::<0>("source.html", None);
@@ -198,6 +869,22 @@ mod tests {
         render(0.0, &mut r, [], &mut output, "source-template-1.png");
     }
 
+    #[test]
+    fn test_source_template_json_data() {
+        let html = std::fs::read_to_string(testdata!().join("source-template.html")).unwrap();
+        let json_data = std::fs::read_to_string(testdata!().join("source-template.json")).unwrap();
+        let base_url = testdata!().join("");
+        let (url, html) = process_template_source(
+            &html,
+            base_url.to_str(),
+            Some(&TemplateData::Json(json_data)),
+        )
+        .unwrap();
+        let mut r = WebVfxRenderer::<0>::new(&url, &html, "5s", WIDTH, HEIGHT);
+        let mut output = RgbaImage::new(WIDTH, HEIGHT);
+        render(0.0, &mut r, [], &mut output, "source-template-1.png");
+    }
+
     #[test]
     fn test_filter() {
         let (mut r, mut output) = init_renderer::<1>("filter.html", None);
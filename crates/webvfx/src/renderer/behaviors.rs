@@ -0,0 +1,428 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{collections::HashMap, fmt::Write};
+
+use blitz_dom::{BaseDocument, LocalName, Node};
+
+use super::{
+    easing::Easing,
+    subtitles::parse_timestamp,
+    telemetry::{WEBVFX_PRECISION_ATTRIBUTE, parse_time},
+    timeline::set_text_content,
+    timing::parse_css_duration,
+};
+
+pub const WEBVFX_TYPEWRITER_ATTRIBUTE: &str = "data-webvfx-typewriter";
+pub const WEBVFX_COUNT_FROM_ATTRIBUTE: &str = "data-webvfx-count-from";
+pub const WEBVFX_COUNT_TO_ATTRIBUTE: &str = "data-webvfx-count-to";
+pub const WEBVFX_TIMECODE_ATTRIBUTE: &str = "data-webvfx-timecode";
+pub const WEBVFX_CLOCK_ATTRIBUTE: &str = "data-webvfx-clock";
+pub const WEBVFX_COUNTDOWN_ATTRIBUTE: &str = "data-webvfx-countdown";
+pub const WEBVFX_DELAY_ATTRIBUTE: &str = "data-webvfx-delay";
+pub const WEBVFX_DURATION_ATTRIBUTE: &str = "data-webvfx-duration";
+pub const WEBVFX_EASING_ATTRIBUTE: &str = "data-webvfx-easing";
+pub const WEBVFX_SEPARATOR_ATTRIBUTE: &str = "data-webvfx-separator";
+pub const WEBVFX_FORMAT_ATTRIBUTE: &str = "data-webvfx-format";
+
+const BEHAVIORS_SELECTOR: &str = "[data-webvfx-typewriter], [data-webvfx-count-to], \
+    [data-webvfx-timecode], [data-webvfx-clock], [data-webvfx-countdown]";
+const DEFAULT_CLOCK_FORMAT: &str = "%H:%M:%S";
+
+#[derive(Debug, PartialEq)]
+enum Behavior {
+    /// Reveal the characters of the text over the duration
+    Typewriter {
+        text: Vec<char>,
+        delay: f64,
+        duration: Option<f64>,
+    },
+    /// Count from one number to another over the duration
+    Counter {
+        from: f64,
+        to: f64,
+        delay: f64,
+        duration: Option<f64>,
+        easing: Easing,
+        precision: usize,
+        separator: String,
+    },
+    /// SMPTE timecode, at the host frame rate if `None`
+    Timecode { frame_rate: Option<f64> },
+    /// A clock starting at `start` seconds, counting down if `direction` is -1
+    Clock {
+        start: f64,
+        direction: f64,
+        format: String,
+    },
+}
+
+/// Text content driven by time, set with attributes:
+/// - `data-webvfx-typewriter` reveals the text of the element over a duration,
+///   markup in the element is replaced by its text.
+/// - `data-webvfx-count-from` and `data-webvfx-count-to` count between two numbers over
+///   a duration, formatted with `data-webvfx-precision` decimal places and
+///   `data-webvfx-separator` between thousands. `data-webvfx-easing` eases the count.
+/// - `data-webvfx-timecode` shows SMPTE timecode at the frame rate of its value, or the host
+///   frame rate if empty. 29.97 and 59.94 use drop frame timecode.
+/// - `data-webvfx-clock` shows a clock starting at its value, an RFC 3339 date-time or a time
+///   of day such as `09:30:00`. `data-webvfx-countdown` counts down from its value,
+///   in seconds or `hh:mm:ss`, to zero. Both are formatted with `data-webvfx-format`
+///   (`%Y`, `%m`, `%d`, `%H`, `%I`, `%p`, `%M`, `%S`, `%f` milliseconds and `%%`),
+///   default `%H:%M:%S`.
+///
+/// Durations are the value of the typewriter attribute or `data-webvfx-duration`,
+/// defaulting to the clip duration. Animations start after `data-webvfx-delay`.
+#[derive(Default)]
+pub struct TextBehaviors {
+    elements: Vec<(usize, Behavior)>,
+    applied: HashMap<usize, String>,
+}
+
+impl TextBehaviors {
+    pub fn new(document: &BaseDocument) -> Self {
+        let mut behaviors = Self::default();
+        behaviors.bind(document);
+        behaviors
+    }
+
+    /// Find the elements with behaviors in a new document
+    pub fn bind(&mut self, document: &BaseDocument) {
+        self.applied.clear();
+        self.elements = document
+            .query_selector_all(BEHAVIORS_SELECTOR)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|node_id| {
+                let node = document.get_node(*node_id)?;
+                match Behavior::from_node(node) {
+                    Ok(behavior) => Some((*node_id, behavior)),
+                    Err(e) => {
+                        log::warn!("invalid text behavior: {e}");
+                        None
+                    }
+                }
+            })
+            .collect();
+    }
+
+    /// Apply the behaviors at `time`, text is only written when it changes
+    pub fn apply(
+        &mut self,
+        time: f64,
+        frame_rate: f64,
+        clip_duration: Option<f64>,
+        document: &mut BaseDocument,
+    ) {
+        for (node_id, behavior) in &self.elements {
+            let text = behavior.text(time, frame_rate, clip_duration);
+            if self.applied.get(node_id) != Some(&text) {
+                set_text_content(document, *node_id, &text);
+                self.applied.insert(*node_id, text);
+            }
+        }
+    }
+}
+
+impl Behavior {
+    fn from_node(node: &Node) -> anyhow::Result<Self> {
+        let attr = |name: &str| node.attr(LocalName::from(name)).map(str::trim);
+        let delay = attr(WEBVFX_DELAY_ATTRIBUTE)
+            .map(|delay| parse_duration(delay).ok_or_else(|| anyhow::anyhow!("delay '{delay}'")))
+            .transpose()?
+            .unwrap_or(0.0);
+        let duration = attr(WEBVFX_DURATION_ATTRIBUTE)
+            .filter(|duration| !duration.is_empty())
+            .map(|duration| {
+                parse_duration(duration).ok_or_else(|| anyhow::anyhow!("duration '{duration}'"))
+            })
+            .transpose()?;
+
+        if let Some(typewriter) = attr(WEBVFX_TYPEWRITER_ATTRIBUTE) {
+            let duration = if typewriter.is_empty() {
+                duration
+            } else {
+                Some(
+                    parse_duration(typewriter)
+                        .ok_or_else(|| anyhow::anyhow!("typewriter duration '{typewriter}'"))?,
+                )
+            };
+            Ok(Self::Typewriter {
+                text: node.text_content().trim().chars().collect(),
+                delay,
+                duration,
+            })
+        } else if let Some(to) = attr(WEBVFX_COUNT_TO_ATTRIBUTE) {
+            let number = |value: &str| {
+                value
+                    .parse::<f64>()
+                    .map_err(|_| anyhow::anyhow!("count '{value}'"))
+            };
+            Ok(Self::Counter {
+                from: attr(WEBVFX_COUNT_FROM_ATTRIBUTE).map_or(Ok(0.0), number)?,
+                to: number(to)?,
+                delay,
+                duration,
+                easing: attr(WEBVFX_EASING_ATTRIBUTE).map_or(Ok(Easing::Linear), Easing::parse)?,
+                precision: attr(WEBVFX_PRECISION_ATTRIBUTE)
+                    .and_then(|precision| precision.parse().ok())
+                    .unwrap_or(0),
+                // Not trimmed, so the separator can be a space
+                separator: node
+                    .attr(LocalName::from(WEBVFX_SEPARATOR_ATTRIBUTE))
+                    .map(String::from)
+                    .unwrap_or_default(),
+            })
+        } else if let Some(frame_rate) = attr(WEBVFX_TIMECODE_ATTRIBUTE) {
+            let frame_rate = match frame_rate {
+                "" => None,
+                frame_rate => Some(
+                    frame_rate
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|frame_rate| *frame_rate > 0.0)
+                        .ok_or_else(|| anyhow::anyhow!("timecode frame rate '{frame_rate}'"))?,
+                ),
+            };
+            Ok(Self::Timecode { frame_rate })
+        } else {
+            let (value, direction) = match attr(WEBVFX_CLOCK_ATTRIBUTE) {
+                Some(clock) => (clock, 1.0),
+                None => (attr(WEBVFX_COUNTDOWN_ATTRIBUTE).unwrap_or_default(), -1.0),
+            };
+            let start = parse_time(value)
+                .or_else(|| parse_timestamp(value))
+                .ok_or_else(|| anyhow::anyhow!("clock '{value}'"))?;
+            Ok(Self::Clock {
+                start,
+                direction,
+                format: attr(WEBVFX_FORMAT_ATTRIBUTE)
+                    .unwrap_or(DEFAULT_CLOCK_FORMAT)
+                    .to_string(),
+            })
+        }
+    }
+
+    fn text(&self, time: f64, host_frame_rate: f64, clip_duration: Option<f64>) -> String {
+        let progress = |delay: f64, duration: Option<f64>| match duration.or(clip_duration) {
+            Some(duration) if duration > 0.0 => ((time - delay) / duration).clamp(0.0, 1.0),
+            _ => 1.0,
+        };
+        match self {
+            Self::Typewriter {
+                text,
+                delay,
+                duration,
+            } => {
+                #[allow(clippy::cast_precision_loss, clippy::cast_sign_loss)]
+                let count = (progress(*delay, *duration) * text.len() as f64).floor() as usize;
+                text[..count.min(text.len())].iter().collect()
+            }
+            Self::Counter {
+                from,
+                to,
+                delay,
+                duration,
+                easing,
+                precision,
+                separator,
+            } => {
+                let value = from + (to - from) * easing.apply(progress(*delay, *duration));
+                format_number(value, *precision, separator)
+            }
+            Self::Timecode { frame_rate } => timecode(time, frame_rate.unwrap_or(host_frame_rate)),
+            Self::Clock {
+                start,
+                direction,
+                format,
+            } => {
+                let seconds = start + time * direction;
+                format_clock(
+                    if *direction < 0.0 {
+                        seconds.max(0.0)
+                    } else {
+                        seconds
+                    },
+                    format,
+                )
+            }
+        }
+    }
+}
+
+/// Seconds, or a CSS `<time>` such as `500ms`
+pub(super) fn parse_duration(duration: &str) -> Option<f64> {
+    parse_css_duration(duration).or_else(|| duration.trim().parse().ok())
+}
+
+fn format_number(value: f64, precision: usize, separator: &str) -> String {
+    let formatted = format!("{:.precision$}", value.abs());
+    let (integer, fraction) = formatted
+        .split_once('.')
+        .map_or((formatted.as_str(), None), |(integer, fraction)| {
+            (integer, Some(fraction))
+        });
+    let mut number = String::new();
+    if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        number.push('-');
+    }
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            number.push_str(separator);
+        }
+        number.push(digit);
+    }
+    if let Some(fraction) = fraction {
+        number.push('.');
+        number.push_str(fraction);
+    }
+    number
+}
+
+/// SMPTE timecode of the frame at `time`, using drop frame timecode for 29.97 and 59.94
+#[allow(clippy::cast_sign_loss)]
+fn timecode(time: f64, frame_rate: f64) -> String {
+    let nominal = (frame_rate.round() as u64).max(1);
+    let drop_frame = (frame_rate - frame_rate.round()).abs() > 1e-3 && nominal % 30 == 0;
+    // Allow for times computed from frame numbers being slightly under
+    let mut frame = (time * frame_rate + 1e-6).floor().max(0.0) as u64;
+    if drop_frame {
+        // Frame numbers 0 and 1 (0-3 at 59.94) are skipped each minute, except every tenth
+        let dropped = nominal / 15;
+        let frames_per_minute = nominal * 60 - dropped;
+        let frames_per_10_minutes = nominal * 600 - dropped * 9;
+        let tens = frame / frames_per_10_minutes;
+        let remainder = frame % frames_per_10_minutes;
+        frame += dropped * 9 * tens;
+        if remainder > dropped {
+            frame += dropped * ((remainder - dropped) / frames_per_minute);
+        }
+    }
+    let seconds = frame / nominal;
+    format!(
+        "{:02}:{:02}:{:02}{}{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        if drop_frame { ';' } else { ':' },
+        frame % nominal
+    )
+}
+
+fn format_clock(seconds: f64, format: &str) -> String {
+    let whole = seconds.floor();
+    let millis = ((seconds - whole) * 1000.0) as i64;
+    let whole = whole as i64;
+    let (year, month, day) = civil_from_days(whole.div_euclid(86400));
+    let second_of_day = whole.rem_euclid(86400);
+    let (hour, minute, second) = (
+        second_of_day / 3600,
+        second_of_day / 60 % 60,
+        second_of_day % 60,
+    );
+
+    let mut clock = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            clock.push(c);
+            continue;
+        }
+        let _ = match chars.next() {
+            Some('Y') => write!(clock, "{year:04}"),
+            Some('m') => write!(clock, "{month:02}"),
+            Some('d') => write!(clock, "{day:02}"),
+            Some('H') => write!(clock, "{hour:02}"),
+            Some('I') => write!(clock, "{:02}", (hour + 11) % 12 + 1),
+            Some('p') => write!(clock, "{}", if hour < 12 { "AM" } else { "PM" }),
+            Some('M') => write!(clock, "{minute:02}"),
+            Some('S') => write!(clock, "{second:02}"),
+            Some('f') => write!(clock, "{millis:03}"),
+            Some('%') | None => write!(clock, "%"),
+            Some(other) => write!(clock, "%{other}"),
+        };
+    }
+    clock
+}
+
+/// Proleptic Gregorian date of days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use blitz_dom::DocumentConfig;
+    use blitz_html::HtmlDocument;
+
+    use super::*;
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(1_234_567.891, 2, ","), "1,234,567.89");
+        assert_eq!(format_number(-999.4, 0, ","), "-999");
+        assert_eq!(format_number(-0.2, 0, ","), "0");
+        assert_eq!(format_number(12_345.0, 0, ""), "12345");
+    }
+
+    #[test]
+    fn test_timecode() {
+        assert_eq!(timecode(3661.5, 25.0), "01:01:01:12");
+        assert_eq!(timecode(1800.0 / 29.97, 29.97), "00:01:00;02");
+        assert_eq!(timecode(17982.0 / 29.97, 29.97), "00:10:00;00");
+    }
+
+    #[test]
+    fn test_format_clock() {
+        let start = parse_time("2024-02-29T23:59:58.5Z").unwrap();
+        assert_eq!(
+            format_clock(start + 2.0, "%Y-%m-%d %I:%M:%S.%f %p"),
+            "2024-03-01 12:00:00.500 AM"
+        );
+        assert_eq!(format_clock(90.0, "%M:%S"), "01:30");
+    }
+
+    #[test]
+    fn test_typewriter() {
+        let typewriter = Behavior::Typewriter {
+            text: "Hello".chars().collect(),
+            delay: 1.0,
+            duration: Some(2.0),
+        };
+        assert_eq!(typewriter.text(0.5, 25.0, None), "");
+        assert_eq!(typewriter.text(2.0, 25.0, None), "He");
+        assert_eq!(typewriter.text(5.0, 25.0, None), "Hello");
+    }
+
+    #[test]
+    fn test_typewriter_markup() {
+        let mut document = HtmlDocument::from_html(
+            r#"<p id="title" data-webvfx-typewriter="1s">Hello <b>world</b></p>"#,
+            DocumentConfig::default(),
+        );
+        let node_id = document.query_selector_all("#title").unwrap()[0];
+        let mut behaviors = TextBehaviors::new(&document);
+        let content = |document: &BaseDocument| {
+            let node = document.get_node(node_id).unwrap();
+            (node.children.len(), node.text_content())
+        };
+        behaviors.apply(0.5, 25.0, None, &mut document);
+        assert_eq!(content(&document), (1, "Hello".to_string()));
+        behaviors.apply(1.0, 25.0, None, &mut document);
+        assert_eq!(content(&document), (1, "Hello world".to_string()));
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/// A CSS easing function, `linear`, `ease`, `ease-in`, `ease-out`, `ease-in-out`
+/// or `cubic-bezier(x1, y1, x2, y2)`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    CubicBezier([f64; 4]),
+}
+
+impl Easing {
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        match s {
+            "" | "linear" => Ok(Self::Linear),
+            "ease" => Ok(Self::CubicBezier([0.25, 0.1, 0.25, 1.0])),
+            "ease-in" => Ok(Self::CubicBezier([0.42, 0.0, 1.0, 1.0])),
+            "ease-out" => Ok(Self::CubicBezier([0.0, 0.0, 0.58, 1.0])),
+            "ease-in-out" => Ok(Self::CubicBezier([0.42, 0.0, 0.58, 1.0])),
+            _ => {
+                let invalid = || anyhow::anyhow!("invalid easing '{s}'");
+                let points: Vec<f64> = s
+                    .strip_prefix("cubic-bezier(")
+                    .and_then(|s| s.strip_suffix(')'))
+                    .ok_or_else(invalid)?
+                    .split(',')
+                    .map(|v| v.trim().parse::<f64>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| invalid())?;
+                let points: [f64; 4] = points.try_into().map_err(|_| invalid())?;
+                if !(0.0..=1.0).contains(&points[0]) || !(0.0..=1.0).contains(&points[2]) {
+                    return Err(anyhow::anyhow!(
+                        "invalid easing '{s}', x values must be in the range 0..1"
+                    ));
+                }
+                Ok(Self::CubicBezier(points))
+            }
+        }
+    }
+
+    /// Ease `x` in the range 0..1
+    pub fn apply(self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => x,
+            Self::CubicBezier(points) => cubic_bezier(points, x),
+        }
+    }
+}
+
+/// Solve the curve for `t` at `x` and return `y`
+fn cubic_bezier([x1, y1, x2, y2]: [f64; 4], x: f64) -> f64 {
+    const EPSILON: f64 = 1e-7;
+    let bezier = |p1: f64, p2: f64, t: f64| {
+        let u = 1.0 - t;
+        3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t
+    };
+    let derivative = |p1: f64, p2: f64, t: f64| {
+        let u = 1.0 - t;
+        3.0 * u * u * p1 + 6.0 * u * t * (p2 - p1) + 3.0 * t * t * (1.0 - p2)
+    };
+
+    // Newton's method converges quickly for most curves
+    let mut t = x;
+    for _ in 0..8 {
+        let error = bezier(x1, x2, t) - x;
+        if error.abs() < EPSILON {
+            return bezier(y1, y2, t);
+        }
+        let slope = derivative(x1, x2, t);
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        t -= error / slope;
+    }
+
+    // Fall back to bisection, x is monotonic in t since x1 and x2 are in 0..1
+    let (mut low, mut high) = (0.0, 1.0);
+    t = x;
+    for _ in 0..64 {
+        let value = bezier(x1, x2, t);
+        if (value - x).abs() < EPSILON {
+            break;
+        }
+        if value < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = f64::midpoint(low, high);
+    }
+    bezier(y1, y2, t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Easing::parse("linear").unwrap(), Easing::Linear);
+        assert_eq!(
+            Easing::parse("cubic-bezier(0.1, 0.7, 1.0, 0.1)").unwrap(),
+            Easing::CubicBezier([0.1, 0.7, 1.0, 0.1])
+        );
+        assert!(Easing::parse("cubic-bezier(1.5, 0, 0, 1)").is_err());
+        assert!(Easing::parse("bounce").is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        assert!((Easing::Linear.apply(0.3) - 0.3).abs() < 1e-9);
+        let ease_in_out = Easing::parse("ease-in-out").unwrap();
+        assert!(ease_in_out.apply(0.0).abs() < 1e-6);
+        assert!((ease_in_out.apply(0.5) - 0.5).abs() < 1e-6);
+        assert!((ease_in_out.apply(1.0) - 1.0).abs() < 1e-6);
+        assert!(ease_in_out.apply(0.25) < 0.25);
+        assert!(ease_in_out.apply(0.75) > 0.75);
+    }
+}
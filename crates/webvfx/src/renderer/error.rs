@@ -0,0 +1,51 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use blitz_traits::net::Url;
+
+use super::{WebVfxRenderer, params::ParamValues, timing::TimeParams};
+
+/// Render a card displaying `message` into `outframe`, so a broken effect is obvious in the output
+pub fn render_error_frame(message: &str, width: u32, height: u32, outframe: &mut [u32]) {
+    let html = format!(
+        r#"<!doctype html>
+<html>
+    <body style="margin: 0; height: 100%; background: #b00020; color: white; font-family: sans-serif;">
+        <div style="padding: 1em;">
+            <div style="font-size: 1.5em; font-weight: bold;">WebVfx error</div>
+            <pre style="white-space: pre-wrap; font-family: monospace;">{}</pre>
+        </div>
+    </body>
+</html>"#,
+        escape_html(message)
+    );
+    let url = Url::parse("about:blank").unwrap();
+    let mut renderer = WebVfxRenderer::<0>::new(&url, &html, "0s", width, height);
+    // SAFETY: u8 has no alignment requirement and every bit pattern is valid for both types,
+    // the slice covers exactly the bytes of outframe, which stays mutably borrowed while it is used
+    let output = unsafe {
+        std::slice::from_raw_parts_mut(outframe.as_mut_ptr().cast::<u8>(), size_of_val(outframe))
+    };
+    renderer.update(
+        0.0,
+        &ParamValues::default(),
+        &TimeParams::default(),
+        None,
+        [],
+        output,
+    );
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
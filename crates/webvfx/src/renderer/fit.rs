@@ -0,0 +1,239 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use blitz_dom::{BaseDocument, LocalName};
+
+use super::style::InlineStyles;
+
+pub const WEBVFX_FIT_SELECTOR: &str = "[data-webvfx-fit]";
+/// Smallest font size in pixels, default 8
+pub const WEBVFX_FIT_MIN_ATTRIBUTE: &str = "data-webvfx-fit-min";
+/// Largest font size in pixels, default 200
+pub const WEBVFX_FIT_MAX_ATTRIBUTE: &str = "data-webvfx-fit-max";
+
+const DEFAULT_MIN_FONT_SIZE: u32 = 8;
+const DEFAULT_MAX_FONT_SIZE: u32 = 200;
+
+/// A fitted element and the font size found for its text and box size
+struct Fit {
+    node_id: usize,
+    min: u32,
+    max: u32,
+    /// Text, size as `f32` bits and font size
+    fitted: Option<(String, (u32, u32), u32)>,
+}
+
+/// Sets the font size of `data-webvfx-fit` elements to the largest whole pixel size,
+/// within `data-webvfx-fit-min` and `data-webvfx-fit-max`, at which their text does not
+/// overflow them. The element needs a fixed width and height, text wraps as usual
+/// so use `white-space: nowrap` to fit a single line.
+///
+/// Each search lays out the document several times, so the font size is reused
+/// until the text or size of the element changes.
+#[derive(Default)]
+pub struct FitText {
+    elements: Vec<Fit>,
+}
+
+impl FitText {
+    pub fn new(document: &BaseDocument) -> Self {
+        let mut fit = Self::default();
+        fit.bind(document);
+        fit
+    }
+
+    /// Find the fitted elements in a new document
+    pub fn bind(&mut self, document: &BaseDocument) {
+        self.elements = document
+            .query_selector_all(WEBVFX_FIT_SELECTOR)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|node_id| {
+                let node = document.get_node(*node_id)?;
+                let attr = |name: &str, default: u32| {
+                    node.attr(LocalName::from(name))
+                        .and_then(|value| value.trim().trim_end_matches("px").parse().ok())
+                        .unwrap_or(default)
+                };
+                let min = attr(WEBVFX_FIT_MIN_ATTRIBUTE, DEFAULT_MIN_FONT_SIZE).max(1);
+                Some(Fit {
+                    node_id: *node_id,
+                    min,
+                    max: attr(WEBVFX_FIT_MAX_ATTRIBUTE, DEFAULT_MAX_FONT_SIZE).max(min),
+                    fitted: None,
+                })
+            })
+            .collect();
+    }
+
+    /// Push the font sizes of elements whose text and size are unchanged since they were fitted,
+    /// before the document is laid out
+    pub fn push_styles(&self, document: &BaseDocument, inline_styles: &mut InlineStyles) {
+        for fit in &self.elements {
+            if let Some(font_size) = fit.current_font_size(document) {
+                inline_styles.push(fit.node_id, &font_size_declaration(font_size));
+            }
+        }
+    }
+
+    /// Search for the font size of elements whose text or size changed,
+    /// after the document is laid out at `time`
+    pub fn fit(
+        &mut self,
+        time: f64,
+        document: &mut BaseDocument,
+        inline_styles: &mut InlineStyles,
+    ) {
+        for fit in &mut self.elements {
+            if fit.current_font_size(document).is_some() {
+                continue;
+            }
+            let Some(node) = document.get_node(fit.node_id) else {
+                continue;
+            };
+            let text = node.text_content();
+            let size = node.final_layout.size;
+            let base = inline_styles
+                .applied(fit.node_id)
+                .unwrap_or_default()
+                .to_string();
+            let mut try_font_size = |font_size: u32| {
+                inline_styles.write(
+                    document,
+                    fit.node_id,
+                    format!("{base}; {}", font_size_declaration(font_size)),
+                );
+                document.resolve(time);
+                document.get_node(fit.node_id).is_some_and(|node| {
+                    let layout = &node.final_layout;
+                    layout.content_size.width <= layout.size.width + 0.5
+                        && layout.content_size.height <= layout.size.height + 0.5
+                })
+            };
+
+            // The largest size that fits, or the minimum if none do
+            let font_size = if try_font_size(fit.max) {
+                fit.max
+            } else {
+                let (mut low, mut high) = (fit.min, fit.max);
+                while high - low > 1 {
+                    let middle = u32::midpoint(low, high);
+                    if try_font_size(middle) {
+                        low = middle;
+                    } else {
+                        high = middle;
+                    }
+                }
+                if !try_font_size(low) {
+                    log::debug!("text '{text}' does not fit at the minimum font size");
+                }
+                low
+            };
+            fit.fitted = Some((
+                text,
+                (size.width.to_bits(), size.height.to_bits()),
+                font_size,
+            ));
+        }
+    }
+}
+
+impl Fit {
+    /// The fitted font size, if the text and size of the element have not changed
+    fn current_font_size(&self, document: &BaseDocument) -> Option<u32> {
+        let (text, size, font_size) = self.fitted.as_ref()?;
+        let node = document.get_node(self.node_id)?;
+        let layout_size = node.final_layout.size;
+        (*size == (layout_size.width.to_bits(), layout_size.height.to_bits())
+            && node.text_content() == *text)
+            .then_some(*font_size)
+    }
+}
+
+fn font_size_declaration(font_size: u32) -> String {
+    format!("font-size: {font_size}px;")
+}
+
+#[cfg(test)]
+mod tests {
+    use blitz_dom::DocumentConfig;
+    use blitz_html::HtmlDocument;
+    use blitz_traits::shell::{ColorScheme, Viewport};
+
+    use super::*;
+    use crate::renderer::timeline::set_text_content;
+
+    fn document(html: &str) -> HtmlDocument {
+        let mut document = HtmlDocument::from_html(
+            html,
+            DocumentConfig {
+                viewport: Some(Viewport::new(320, 240, 1.0, ColorScheme::Light)),
+                ..Default::default()
+            },
+        );
+        document.resolve(0.0);
+        document
+    }
+
+    /// Fit a frame the way the renderer does, returning the font size
+    fn fit_frame(fit: &mut FitText, document: &mut BaseDocument, styles: &mut InlineStyles) -> u32 {
+        fit.push_styles(document, styles);
+        styles.apply(document);
+        document.resolve(0.0);
+        fit.fit(0.0, document, styles);
+        fit.elements[0].fitted.as_ref().unwrap().2
+    }
+
+    const TITLE: &str = r#"<div id="title" style="width: 160px; height: 40px; white-space: nowrap"
+        data-webvfx-fit data-webvfx-fit-max="100">Fitted title</div>"#;
+
+    #[test]
+    fn test_reuse() {
+        let mut document = document(TITLE);
+        let mut fit = FitText::new(&document);
+        let mut styles = InlineStyles::default();
+        let font_size = fit_frame(&mut fit, &mut document, &mut styles);
+        assert!((DEFAULT_MIN_FONT_SIZE..100).contains(&font_size));
+
+        // A search would replace the size, so it is reused while the text and box are unchanged
+        fit.elements[0].fitted.as_mut().unwrap().2 = font_size - 1;
+        assert_eq!(
+            fit_frame(&mut fit, &mut document, &mut styles),
+            font_size - 1
+        );
+        assert!(
+            styles
+                .applied(fit.elements[0].node_id)
+                .unwrap()
+                .contains(&font_size_declaration(font_size - 1))
+        );
+    }
+
+    #[test]
+    fn test_text_changed() {
+        let mut document = document(TITLE);
+        let mut fit = FitText::new(&document);
+        let mut styles = InlineStyles::default();
+        let font_size = fit_frame(&mut fit, &mut document, &mut styles);
+
+        let node_id = fit.elements[0].node_id;
+        set_text_content(&mut document, node_id, "A much longer fitted title");
+        let longer_font_size = fit_frame(&mut fit, &mut document, &mut styles);
+        assert!(longer_font_size < font_size);
+        assert_eq!(
+            fit.elements[0].fitted.as_ref().unwrap().0,
+            "A much longer fitted title"
+        );
+    }
+
+    #[test]
+    fn test_min() {
+        let mut document = document(
+            r#"<div style="width: 10px; height: 10px; white-space: nowrap"
+                data-webvfx-fit data-webvfx-fit-min="12">Does not fit</div>"#,
+        );
+        let mut fit = FitText::new(&document);
+        let mut styles = InlineStyles::default();
+        assert_eq!(fit_frame(&mut fit, &mut document, &mut styles), 12);
+    }
+}
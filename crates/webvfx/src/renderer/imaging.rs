@@ -0,0 +1,295 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! CPU image operations on RGBA8 frames of `width` by `height` pixels.
+//! Alpha is left unchanged except by `gaussian_blur`.
+
+/// Blur with a gaussian of standard deviation `sigma` pixels, edge pixels are extended
+#[allow(clippy::cast_precision_loss, clippy::cast_sign_loss)]
+pub fn gaussian_blur(frame: &mut [u8], width: usize, height: usize, sigma: f32) {
+    if sigma <= 0.0 {
+        return;
+    }
+    let radius = (sigma * 3.0).ceil() as usize;
+    let mut kernel: Vec<f32> = (0..=2 * radius)
+        .map(|i| {
+            let x = i as f32 - radius as f32;
+            (-x * x / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+    let sum: f32 = kernel.iter().sum();
+    kernel.iter_mut().for_each(|weight| *weight /= sum);
+    convolve(frame, width, height, &kernel);
+}
+
+/// Blur with a box `2 * radius + 1` pixels wide, edge pixels are extended
+#[allow(clippy::cast_precision_loss)]
+pub fn box_blur(frame: &mut [u8], width: usize, height: usize, radius: usize) {
+    if radius == 0 {
+        return;
+    }
+    let size = 2 * radius + 1;
+    convolve(frame, width, height, &vec![1.0 / size as f32; size]);
+}
+
+/// Convolve all channels horizontally and then vertically with a `kernel` of odd length
+fn convolve(frame: &mut [u8], width: usize, height: usize, kernel: &[f32]) {
+    if width == 0 || height == 0 {
+        return;
+    }
+    let radius = kernel.len() / 2;
+    let mut horizontal = vec![0.0f32; frame.len()];
+    for y in 0..height {
+        let row = y * width;
+        for x in 0..width {
+            let mut sum = [0.0f32; 4];
+            for (k, weight) in kernel.iter().enumerate() {
+                let sample = (x + k).saturating_sub(radius).min(width - 1);
+                for (sum, value) in sum.iter_mut().zip(&frame[(row + sample) * 4..][..4]) {
+                    *sum += weight * f32::from(*value);
+                }
+            }
+            horizontal[(row + x) * 4..][..4].copy_from_slice(&sum);
+        }
+    }
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0.0f32; 4];
+            for (k, weight) in kernel.iter().enumerate() {
+                let sample = (y + k).saturating_sub(radius).min(height - 1);
+                for (sum, value) in sum
+                    .iter_mut()
+                    .zip(&horizontal[(sample * width + x) * 4..][..4])
+                {
+                    *sum += weight * value;
+                }
+            }
+            for (value, sum) in frame[(y * width + x) * 4..][..4].iter_mut().zip(sum) {
+                *value = to_u8(sum);
+            }
+        }
+    }
+}
+
+/// Sharpen by adding `amount` times the difference from a gaussian blur of `sigma` pixels
+pub fn unsharp_mask(frame: &mut [u8], width: usize, height: usize, sigma: f32, amount: f32) {
+    let mut blurred = frame.to_vec();
+    gaussian_blur(&mut blurred, width, height, sigma);
+    for (pixel, blurred) in frame.chunks_exact_mut(4).zip(blurred.chunks_exact(4)) {
+        for (value, blurred) in pixel[..3].iter_mut().zip(&blurred[..3]) {
+            let value_f = f32::from(*value);
+            *value = to_u8(value_f + amount * (value_f - f32::from(*blurred)));
+        }
+    }
+}
+
+/// Darken towards the corners by up to `amount`, starting at `radius`
+/// (0 at the center, 1 at the corners)
+#[allow(clippy::cast_precision_loss)]
+pub fn vignette(frame: &mut [u8], width: usize, height: usize, amount: f32, radius: f32) {
+    if width == 0 || height == 0 {
+        return;
+    }
+    let (center_x, center_y) = (width as f32 / 2.0, height as f32 / 2.0);
+    let corner = center_x.hypot(center_y);
+    for (i, pixel) in frame.chunks_exact_mut(4).enumerate() {
+        let x = (i % width) as f32 + 0.5 - center_x;
+        let y = (i / width) as f32 + 0.5 - center_y;
+        let scale = 1.0 - amount * smoothstep(radius, 1.0, x.hypot(y) / corner);
+        for value in &mut pixel[..3] {
+            *value = to_u8(f32::from(*value) * scale);
+        }
+    }
+}
+
+/// Add monochrome noise of up to `amount` (0..1), `seed` selects the noise pattern
+pub fn grain(frame: &mut [u8], amount: f32, seed: u64) {
+    let strength = amount * 255.0;
+    for (i, pixel) in (0u64..).zip(frame.chunks_exact_mut(4)) {
+        let noise = noise(seed, i) * strength;
+        for value in &mut pixel[..3] {
+            *value = to_u8(f32::from(*value) + noise);
+        }
+    }
+}
+
+/// Affine transform of RGB values in the range 0..1, each row is the
+/// red, green and blue coefficients and an offset
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorMatrix(pub [[f32; 4]; 3]);
+
+impl ColorMatrix {
+    pub const IDENTITY: Self = Self([
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+    ]);
+
+    /// Multiply values by `amount`, as the CSS `brightness()` filter
+    pub fn brightness(amount: f32) -> Self {
+        Self([
+            [amount, 0.0, 0.0, 0.0],
+            [0.0, amount, 0.0, 0.0],
+            [0.0, 0.0, amount, 0.0],
+        ])
+    }
+
+    /// Scale values around the midpoint by `amount`, as the CSS `contrast()` filter
+    pub fn contrast(amount: f32) -> Self {
+        let offset = 0.5 - 0.5 * amount;
+        Self([
+            [amount, 0.0, 0.0, offset],
+            [0.0, amount, 0.0, offset],
+            [0.0, 0.0, amount, offset],
+        ])
+    }
+
+    /// Scale saturation by `amount`, as the CSS `saturate()` filter
+    pub fn saturate(amount: f32) -> Self {
+        let s = amount;
+        Self([
+            [0.213 + 0.787 * s, 0.715 - 0.715 * s, 0.072 - 0.072 * s, 0.0],
+            [0.213 - 0.213 * s, 0.715 + 0.285 * s, 0.072 - 0.072 * s, 0.0],
+            [0.213 - 0.213 * s, 0.715 - 0.715 * s, 0.072 + 0.928 * s, 0.0],
+        ])
+    }
+
+    /// This transform followed by `next`
+    #[must_use]
+    pub fn then(&self, next: &Self) -> Self {
+        let mut matrix = [[0.0; 4]; 3];
+        for (row, next_row) in matrix.iter_mut().zip(&next.0) {
+            for (column, value) in row.iter_mut().enumerate() {
+                *value = (0..3).map(|i| next_row[i] * self.0[i][column]).sum();
+            }
+            row[3] += next_row[3];
+        }
+        Self(matrix)
+    }
+
+    pub fn apply(&self, frame: &mut [u8]) {
+        for pixel in frame.chunks_exact_mut(4) {
+            let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(|v| f32::from(v) / 255.0);
+            for (value, row) in pixel[..3].iter_mut().zip(&self.0) {
+                *value = to_u8((row[0] * r + row[1] * g + row[2] * b + row[3]) * 255.0);
+            }
+        }
+    }
+}
+
+/// Make pixels close in chroma to `key` (RGB 0..1) transparent. Pixels closer than
+/// `threshold` are fully transparent, fading to opaque over `softness`.
+/// `spill` (0..1) removes the key color reflected onto the remaining pixels.
+pub fn chroma_key(frame: &mut [u8], key: [f32; 3], threshold: f32, softness: f32, spill: f32) {
+    let key_chroma = chroma(key);
+    // The key channel is limited to the average of the other two to suppress spill
+    let dominant = (0..3).fold(0, |max, i| if key[i] > key[max] { i } else { max });
+    let others = [(dominant + 1) % 3, (dominant + 2) % 3];
+    for pixel in frame.chunks_exact_mut(4) {
+        let rgb = [pixel[0], pixel[1], pixel[2]].map(|v| f32::from(v) / 255.0);
+        let pixel_chroma = chroma(rgb);
+        let distance = (pixel_chroma[0] - key_chroma[0]).hypot(pixel_chroma[1] - key_chroma[1]);
+        let alpha = smoothstep(threshold, threshold + softness, distance);
+        pixel[3] = to_u8(f32::from(pixel[3]) * alpha);
+        let limit = (rgb[others[0]] + rgb[others[1]]) / 2.0;
+        if rgb[dominant] > limit {
+            pixel[dominant] = to_u8((rgb[dominant] - spill * (rgb[dominant] - limit)) * 255.0);
+        }
+    }
+}
+
+/// Blue and red difference chroma (Cb, Cr) of an RGB color
+fn chroma([r, g, b]: [f32; 3]) -> [f32; 2] {
+    [
+        -0.168_736 * r - 0.331_264 * g + 0.5 * b,
+        0.5 * r - 0.418_688 * g - 0.081_312 * b,
+    ]
+}
+
+/// Noise in `[-1, 1)` for pixel `index`
+fn noise(seed: u64, index: u64) -> f32 {
+    let mut x = seed ^ index.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+    // 23 random mantissa bits of a float in [1, 2)
+    (f32::from_bits(0x3f80_0000 | (x >> 41) as u32) - 1.0) * 2.0 - 1.0
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    if edge0 >= edge1 {
+        return if x >= edge1 { 1.0 } else { 0.0 };
+    }
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn to_u8(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gaussian_blur() {
+        // A white pixel in the center of a black 5x5 frame
+        let mut frame = vec![0u8; 5 * 5 * 4];
+        frame[(2 * 5 + 2) * 4..][..4].copy_from_slice(&[255; 4]);
+        gaussian_blur(&mut frame, 5, 5, 1.0);
+        let center = frame[(2 * 5 + 2) * 4];
+        let neighbor = frame[(2 * 5 + 3) * 4];
+        let corner = frame[0];
+        assert!(center > neighbor && neighbor > corner, "{frame:?}");
+        let total: u32 = frame.iter().step_by(4).map(|v| u32::from(*v)).sum();
+        assert!((240..=270).contains(&total), "{total}");
+    }
+
+    #[test]
+    fn test_color_matrix() {
+        let mut frame = vec![200, 100, 50, 255];
+        ColorMatrix::saturate(0.0).apply(&mut frame);
+        assert!(frame[0] == frame[1] && frame[1] == frame[2], "{frame:?}");
+
+        let matrix = ColorMatrix::brightness(0.5).then(&ColorMatrix::contrast(2.0));
+        let mut frame = vec![255, 100, 0, 255];
+        matrix.apply(&mut frame);
+        assert_eq!(frame, vec![128, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_chroma_key() {
+        let mut frame = vec![
+            0, 255, 0, 255, // key
+            60, 160, 60, 255, // green screen
+            220, 180, 160, 255, // skin
+            128, 128, 128, 255, // gray
+        ];
+        chroma_key(&mut frame, [0.0, 1.0, 0.0], 0.4, 0.1, 1.0);
+        assert_eq!(frame[3], 0);
+        assert_eq!(frame[7], 0);
+        assert_eq!(&frame[8..], &[220, 180, 160, 255, 128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn test_vignette() {
+        let mut frame = vec![200u8; 9 * 9 * 4];
+        vignette(&mut frame, 9, 9, 1.0, 0.2);
+        assert_eq!(frame[(4 * 9 + 4) * 4], 200);
+        assert!(frame[0] < 20);
+        assert_eq!(frame[3], 200);
+    }
+
+    #[test]
+    fn test_grain() {
+        let mut a = vec![128u8; 64 * 4];
+        let mut b = a.clone();
+        grain(&mut a, 0.1, 1);
+        grain(&mut b, 0.1, 1);
+        assert_eq!(a, b);
+        assert!(a.iter().any(|v| *v != 128));
+        assert!(a.iter().all(|v| (102..=154).contains(v)));
+    }
+}
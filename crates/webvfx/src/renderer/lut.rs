@@ -0,0 +1,250 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::ops::{Add, Mul, Sub};
+
+pub const WEBVFX_LUT_LINK_SELECTOR: &str = r#"link[rel="webvfx-lut"]"#;
+/// `.cube` LUT applied to an input, on `img.webvfx-videoN` elements
+pub const WEBVFX_LUT_ATTRIBUTE: &str = "data-webvfx-lut";
+/// `tetrahedral` (default) or `trilinear`, on LUT links and `img.webvfx-videoN` elements
+pub const WEBVFX_LUT_INTERPOLATION_ATTRIBUTE: &str = "data-webvfx-lut-interpolation";
+
+const MAX_SIZE: usize = 256;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Interpolation {
+    Trilinear,
+    #[default]
+    Tetrahedral,
+}
+
+impl Interpolation {
+    #[allow(clippy::missing_errors_doc)]
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        match s.trim() {
+            "" | "tetrahedral" => Ok(Self::Tetrahedral),
+            "trilinear" => Ok(Self::Trilinear),
+            s => Err(anyhow::anyhow!("invalid LUT interpolation '{s}'")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rgb([f32; 3]);
+
+impl Add for Rgb {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self([0, 1, 2].map(|c| self.0[c] + other.0[c]))
+    }
+}
+
+impl Sub for Rgb {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self([0, 1, 2].map(|c| self.0[c] - other.0[c]))
+    }
+}
+
+impl Mul<f32> for Rgb {
+    type Output = Self;
+    fn mul(self, weight: f32) -> Self {
+        Self(self.0.map(|value| value * weight))
+    }
+}
+
+/// A 3D color lookup table, in the `.cube` format
+#[derive(Debug, PartialEq)]
+pub struct Lut3d {
+    size: usize,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    /// Output colors, red varies fastest
+    table: Vec<Rgb>,
+}
+
+impl Lut3d {
+    #[allow(clippy::missing_errors_doc)]
+    pub fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        let text = String::from_utf8_lossy(data);
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let keyword = fields.next().unwrap_or_default();
+            let values = |fields: std::str::SplitWhitespace| {
+                fields
+                    .map(str::parse::<f32>)
+                    .collect::<Result<Vec<f32>, _>>()
+                    .map_err(|e| anyhow::anyhow!("line {}: {e}", number + 1))
+            };
+            let rgb = |values: Vec<f32>| -> anyhow::Result<[f32; 3]> {
+                values
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("line {}: expected 3 values", number + 1))
+            };
+            match keyword {
+                "TITLE" => {}
+                "LUT_3D_SIZE" => {
+                    let value = fields.next().unwrap_or_default();
+                    size = Some(
+                        value
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|size| (2..=MAX_SIZE).contains(size))
+                            .ok_or_else(|| anyhow::anyhow!("invalid LUT_3D_SIZE '{value}'"))?,
+                    );
+                }
+                "LUT_1D_SIZE" => return Err(anyhow::anyhow!("1D LUTs are not supported")),
+                "DOMAIN_MIN" => domain_min = rgb(values(fields)?)?,
+                "DOMAIN_MAX" => domain_max = rgb(values(fields)?)?,
+                "LUT_3D_INPUT_RANGE" => {
+                    let range = values(fields)?;
+                    let [min, max] = range[..] else {
+                        return Err(anyhow::anyhow!("line {}: expected 2 values", number + 1));
+                    };
+                    domain_min = [min; 3];
+                    domain_max = [max; 3];
+                }
+                keyword if keyword.starts_with(|c: char| c.is_ascii_uppercase()) => {
+                    log::debug!("ignoring LUT keyword {keyword}");
+                }
+                _ => table.push(Rgb(rgb(values(line.split_whitespace())?)?)),
+            }
+        }
+        let size = size.ok_or_else(|| anyhow::anyhow!("missing LUT_3D_SIZE"))?;
+        if table.len() != size * size * size {
+            return Err(anyhow::anyhow!(
+                "expected {} table entries, found {}",
+                size * size * size,
+                table.len()
+            ));
+        }
+        if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+            return Err(anyhow::anyhow!("invalid domain"));
+        }
+        Ok(Self {
+            size,
+            domain_min,
+            domain_max,
+            table,
+        })
+    }
+
+    /// Map the RGB values of an RGBA8 frame through the table
+    #[allow(clippy::cast_precision_loss, clippy::cast_sign_loss)]
+    pub fn apply(&self, frame: &mut [u8], interpolation: Interpolation) {
+        let scale = (self.size - 1) as f32;
+        for pixel in frame.chunks_exact_mut(4) {
+            let position = [0, 1, 2].map(|c| {
+                let value = f32::from(pixel[c]) / 255.0;
+                ((value - self.domain_min[c]) / (self.domain_max[c] - self.domain_min[c]))
+                    .clamp(0.0, 1.0)
+                    * scale
+            });
+            let color = match interpolation {
+                Interpolation::Trilinear => self.trilinear(position),
+                Interpolation::Tetrahedral => self.tetrahedral(position),
+            };
+            for (value, output) in pixel[..3].iter_mut().zip(color.0) {
+                *value = (output * 255.0).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    fn at(&self, r: usize, g: usize, b: usize) -> Rgb {
+        self.table[r + self.size * (g + self.size * b)]
+    }
+
+    /// Lower and upper table indices around `position` and the fraction between them
+    #[allow(clippy::cast_sign_loss)]
+    fn cell(&self, position: f32) -> (usize, usize, f32) {
+        let lower = (position.floor() as usize).min(self.size - 1);
+        (lower, (lower + 1).min(self.size - 1), position.fract())
+    }
+
+    fn trilinear(&self, [r, g, b]: [f32; 3]) -> Rgb {
+        let (r0, r1, fr) = self.cell(r);
+        let (g0, g1, fg) = self.cell(g);
+        let (b0, b1, fb) = self.cell(b);
+        let lerp = |a: Rgb, b: Rgb, t: f32| a + (b - a) * t;
+        let c00 = lerp(self.at(r0, g0, b0), self.at(r1, g0, b0), fr);
+        let c10 = lerp(self.at(r0, g1, b0), self.at(r1, g1, b0), fr);
+        let c01 = lerp(self.at(r0, g0, b1), self.at(r1, g0, b1), fr);
+        let c11 = lerp(self.at(r0, g1, b1), self.at(r1, g1, b1), fr);
+        lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb)
+    }
+
+    fn tetrahedral(&self, [r, g, b]: [f32; 3]) -> Rgb {
+        let (r0, r1, fr) = self.cell(r);
+        let (g0, g1, fg) = self.cell(g);
+        let (b0, b1, fb) = self.cell(b);
+        let c000 = self.at(r0, g0, b0);
+        let c111 = self.at(r1, g1, b1);
+        // Interpolate in the tetrahedron from c000 through `first` and `second` to c111
+        let interpolate = |first: Rgb, second: Rgb, [w1, w2, w3]: [f32; 3]| {
+            c000 + (first - c000) * w1 + (second - first) * w2 + (c111 - second) * w3
+        };
+        if fr > fg {
+            if fg > fb {
+                interpolate(self.at(r1, g0, b0), self.at(r1, g1, b0), [fr, fg, fb])
+            } else if fr > fb {
+                interpolate(self.at(r1, g0, b0), self.at(r1, g0, b1), [fr, fb, fg])
+            } else {
+                interpolate(self.at(r0, g0, b1), self.at(r1, g0, b1), [fb, fr, fg])
+            }
+        } else if fb > fg {
+            interpolate(self.at(r0, g0, b1), self.at(r0, g1, b1), [fb, fg, fr])
+        } else if fb > fr {
+            interpolate(self.at(r0, g1, b0), self.at(r0, g1, b1), [fg, fb, fr])
+        } else {
+            interpolate(self.at(r0, g1, b0), self.at(r1, g1, b0), [fg, fr, fb])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A size 2 LUT of `f` applied to the corners of the unit cube
+    fn lut(f: impl Fn(f32) -> f32) -> Lut3d {
+        let mut cube = String::from("# test\nTITLE \"test\"\nLUT_3D_SIZE 2\n");
+        for b in [0.0, 1.0] {
+            for g in [0.0, 1.0] {
+                for r in [0.0, 1.0] {
+                    cube.push_str(&format!("{} {} {}\n", f(r), f(g), f(b)));
+                }
+            }
+        }
+        Lut3d::parse(cube.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_apply() {
+        let frame = vec![0, 64, 200, 255, 255, 128, 30, 10];
+        for interpolation in [Interpolation::Trilinear, Interpolation::Tetrahedral] {
+            let mut identity = frame.clone();
+            lut(|v| v).apply(&mut identity, interpolation);
+            assert_eq!(identity, frame);
+
+            let mut inverted = frame.clone();
+            lut(|v| 1.0 - v).apply(&mut inverted, interpolation);
+            assert_eq!(inverted, vec![255, 191, 55, 255, 0, 127, 225, 10]);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Lut3d::parse(b"LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(Lut3d::parse(b"LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").is_err());
+        assert!(Lut3d::parse(b"0 0 0\n").is_err());
+        assert!(Lut3d::parse(b"LUT_3D_SIZE 2\n0 0\n").is_err());
+    }
+}
@@ -1,14 +1,20 @@
 // Copyright (C) 2025 Andrew Wason
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::{collections::HashMap, path::PathBuf, sync::Mutex};
+
 use anyhow::Context;
-use blitz_traits::net::{Body, Bytes, NetHandler, NetProvider, Request};
+use blitz_traits::net::{Body, Bytes, NetHandler, NetProvider, Request, Url};
 use data_url::DataUrl;
 use reqwest::blocking::Response;
 
 #[derive(Default)]
 pub struct SyncNetProvider {
     client: reqwest::blocking::Client,
+    fetched_files: Mutex<Vec<PathBuf>>,
+    /// Responses to `GET` requests by URL, if caching
+    cache: Option<Mutex<HashMap<String, Bytes>>>,
+    errors: Mutex<Vec<String>>,
 }
 
 impl SyncNetProvider {
@@ -16,7 +22,64 @@ impl SyncNetProvider {
         Self::default()
     }
 
+    /// A provider that fetches each URL once, so reloading the same document
+    /// does not fetch again. Changes to fetched files are not seen.
+    pub fn with_cache() -> Self {
+        Self {
+            cache: Some(Mutex::new(HashMap::new())),
+            ..Self::default()
+        }
+    }
+
+    /// Paths of all `file:` URLs fetched so far
+    pub fn fetched_files(&self) -> Vec<PathBuf> {
+        self.fetched_files.lock().unwrap().clone()
+    }
+
+    /// Fetch errors since the last call
+    pub fn take_errors(&self) -> Vec<String> {
+        std::mem::take(&mut self.errors.lock().unwrap())
+    }
+
+    /// Fetch a resource the document does not load itself, e.g. a timeline.
+    /// Failures are recorded like document fetch errors.
+    pub fn fetch_url(&self, url: Url) -> Option<Bytes> {
+        let url_string = url.to_string();
+        match self.fetch_inner(Request::get(url)) {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                self.record_error(&url_string, &e);
+                None
+            }
+        }
+    }
+
+    fn record_error(&self, url: &str, e: &anyhow::Error) {
+        log::warn!("failed to fetch url {url}: {e:?}");
+        self.errors
+            .lock()
+            .unwrap()
+            .push(format!("failed to fetch url {url}: {e:#}"));
+    }
+
     fn fetch_inner(&self, request: Request) -> anyhow::Result<Bytes> {
+        let Some(cache) = self
+            .cache
+            .as_ref()
+            .filter(|_| request.method.as_str() == "GET" && request.url.scheme() != "data")
+        else {
+            return self.fetch_uncached(request);
+        };
+        let url = request.url.to_string();
+        if let Some(bytes) = cache.lock().unwrap().get(&url) {
+            return Ok(bytes.clone());
+        }
+        let bytes = self.fetch_uncached(request)?;
+        cache.lock().unwrap().insert(url, bytes.clone());
+        Ok(bytes)
+    }
+
+    fn fetch_uncached(&self, request: Request) -> anyhow::Result<Bytes> {
         match request.url.scheme() {
             "data" => {
                 let data_url = DataUrl::process(request.url.as_str())?;
@@ -24,12 +87,15 @@ impl SyncNetProvider {
                 Ok(Bytes::from(decoded.0))
             }
             "file" => {
-                let file_content = std::fs::read(
-                    request
-                        .url
-                        .to_file_path()
-                        .map_err(|()| anyhow::anyhow!("cannot convert URL to path"))?,
-                )?;
+                let path = request
+                    .url
+                    .to_file_path()
+                    .map_err(|()| anyhow::anyhow!("cannot convert URL to path"))?;
+                let file_content = std::fs::read(&path)?;
+                let mut fetched_files = self.fetched_files.lock().unwrap();
+                if !fetched_files.contains(&path) {
+                    fetched_files.push(path);
+                }
 
                 Ok(Bytes::from(file_content))
             }
@@ -56,8 +122,37 @@ impl NetProvider for SyncNetProvider {
     fn fetch(&self, _doc_id: usize, request: Request, handler: Box<dyn NetHandler>) {
         let url = request.url.to_string();
         match self.fetch_inner(request) {
-            Err(e) => eprintln!("WebVfx: failed to fetch url {url}: {e:?}"),
-            Ok(bytes) => handler.bytes(url, bytes),
+            Err(e) => self.record_error(&url, &e),
+            Ok(bytes) => {
+                log::debug!("fetched url {url}");
+                handler.bytes(url, bytes);
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache() {
+        let dir = std::env::temp_dir().join(format!("webvfx-net-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.txt");
+        let url = Url::from_file_path(&path).unwrap();
+        let uncached = SyncNetProvider::new();
+        let cached = SyncNetProvider::with_cache();
+
+        std::fs::write(&path, "1").unwrap();
+        assert_eq!(uncached.fetch_url(url.clone()).unwrap(), "1");
+        assert_eq!(cached.fetch_url(url.clone()).unwrap(), "1");
+
+        std::fs::write(&path, "2").unwrap();
+        assert_eq!(uncached.fetch_url(url.clone()).unwrap(), "2");
+        assert_eq!(cached.fetch_url(url).unwrap(), "1");
+        assert_eq!(uncached.fetched_files(), vec![path]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
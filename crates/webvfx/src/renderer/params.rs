@@ -0,0 +1,476 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Write;
+
+use blitz_dom::{BaseDocument, local_name};
+
+use super::{
+    WEBVFX_CSS_ANIMATION_PROPERTY,
+    telemetry::WEBVFX_CSS_TELEMETRY_PREFIX,
+    timing::{
+        WEBVFX_CSS_CLIP_DURATION_PROPERTY, WEBVFX_CSS_FRAME_PROPERTY, WEBVFX_CSS_IN_DELAY_PROPERTY,
+        WEBVFX_CSS_IN_DURATION_PROPERTY, WEBVFX_CSS_IN_PROGRESS_PROPERTY,
+        WEBVFX_CSS_OUT_DELAY_PROPERTY, WEBVFX_CSS_OUT_DURATION_PROPERTY,
+        WEBVFX_CSS_OUT_PROGRESS_PROPERTY, WEBVFX_CSS_PROGRESS_PROPERTY, WEBVFX_CSS_TIME_PROPERTY,
+        WEBVFX_CSS_TRANSITION_PROPERTY,
+    },
+};
+
+pub const DOUBLE_SLOTS: usize = 8;
+pub const COLOR_SLOTS: usize = 4;
+pub const POSITION_SLOTS: usize = 4;
+pub const BOOL_SLOTS: usize = 4;
+
+pub const WEBVFX_CSS_PARAM_PREFIX: &str = "--webvfx-param";
+pub const WEBVFX_CSS_COLOR_PREFIX: &str = "--webvfx-color";
+pub const WEBVFX_CSS_POSITION_PREFIX: &str = "--webvfx-position";
+pub const WEBVFX_CSS_BOOL_PREFIX: &str = "--webvfx-bool";
+pub const WEBVFX_PARAM_META_SELECTOR: &str = r#"meta[name="webvfx-param"]"#;
+
+/// Values of the generic plugin parameter slots, exposed to the document as CSS custom properties.
+/// Slots the host has not set are `None` and take the default of any parameter declared on them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParamValues {
+    pub doubles: [Option<f64>; DOUBLE_SLOTS],
+    /// RGB components in the range 0..1
+    pub colors: [Option<[f32; 3]>; COLOR_SLOTS],
+    /// x, y in the range 0..1
+    pub positions: [Option<[f64; 2]>; POSITION_SLOTS],
+    pub bools: [Option<bool>; BOOL_SLOTS],
+}
+
+impl ParamValues {
+    /// Slot values with the defaults and ranges of declared params applied
+    pub fn resolve_slots(&self, declarations: &[ParamDeclaration]) -> ParamValues {
+        let mut resolved = self.clone();
+        for declaration in declarations {
+            match declaration.resolve(self) {
+                ParamValue::Double(v) => resolved.doubles[declaration.slot] = Some(v),
+                ParamValue::Color(v) => resolved.colors[declaration.slot] = Some(v),
+                ParamValue::Position(v) => resolved.positions[declaration.slot] = Some(v),
+                ParamValue::Bool(v) => resolved.bools[declaration.slot] = Some(v),
+            }
+        }
+        resolved
+    }
+
+    pub fn css_declarations(&self, declarations: &[ParamDeclaration]) -> String {
+        let mut css = String::new();
+        for declaration in declarations {
+            declaration
+                .resolve(self)
+                .write_css(&mut css, &format!("--webvfx-{}", declaration.name));
+        }
+        let resolved = self.resolve_slots(declarations);
+        let doubles = resolved.doubles.map(Option::unwrap_or_default);
+        let colors = resolved.colors.map(Option::unwrap_or_default);
+        let positions = resolved.positions.map(Option::unwrap_or_default);
+        let bools = resolved.bools.map(Option::unwrap_or_default);
+        for (i, value) in doubles.into_iter().enumerate() {
+            ParamValue::Double(value)
+                .write_css(&mut css, &format!("{WEBVFX_CSS_PARAM_PREFIX}{}", i + 1));
+        }
+        for (i, value) in colors.into_iter().enumerate() {
+            ParamValue::Color(value)
+                .write_css(&mut css, &format!("{WEBVFX_CSS_COLOR_PREFIX}{}", i + 1));
+        }
+        for (i, value) in positions.into_iter().enumerate() {
+            ParamValue::Position(value)
+                .write_css(&mut css, &format!("{WEBVFX_CSS_POSITION_PREFIX}{}", i + 1));
+        }
+        for (i, value) in bools.into_iter().enumerate() {
+            ParamValue::Bool(value)
+                .write_css(&mut css, &format!("{WEBVFX_CSS_BOOL_PREFIX}{}", i + 1));
+        }
+        css
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamType {
+    Double,
+    Color,
+    Position,
+    Bool,
+}
+
+impl ParamType {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "double" => Ok(Self::Double),
+            "color" => Ok(Self::Color),
+            "position" => Ok(Self::Position),
+            "bool" => Ok(Self::Bool),
+            _ => Err(anyhow::anyhow!("unknown param type '{s}'")),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Double => "double",
+            Self::Color => "color",
+            Self::Position => "position",
+            Self::Bool => "bool",
+        }
+    }
+
+    fn slot_count(self) -> usize {
+        match self {
+            Self::Double => DOUBLE_SLOTS,
+            Self::Color => COLOR_SLOTS,
+            Self::Position => POSITION_SLOTS,
+            Self::Bool => BOOL_SLOTS,
+        }
+    }
+
+    /// Name of the frei0r parameter for the given 0 based slot of this type
+    fn slot_param_name(self, slot: usize) -> String {
+        match self {
+            Self::Double => format!("param{}", slot + 1),
+            Self::Color => format!("color{}", slot + 1),
+            Self::Position => format!("position{}", slot + 1),
+            Self::Bool => format!("bool{}", slot + 1),
+        }
+    }
+
+    fn default_value(self) -> ParamValue {
+        match self {
+            Self::Double => ParamValue::Double(0.0),
+            Self::Color => ParamValue::Color([0.0; 3]),
+            Self::Position => ParamValue::Position([0.0; 2]),
+            Self::Bool => ParamValue::Bool(false),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParamValue {
+    Double(f64),
+    Color([f32; 3]),
+    Position([f64; 2]),
+    Bool(bool),
+}
+
+impl ParamValue {
+    fn parse(param_type: ParamType, s: &str) -> anyhow::Result<Self> {
+        let invalid = || anyhow::anyhow!("invalid {} value '{s}'", param_type.as_str());
+        match param_type {
+            ParamType::Double => Ok(Self::Double(s.parse().map_err(|_| invalid())?)),
+            ParamType::Color => parse_hex_color(s).map(Self::Color).ok_or_else(invalid),
+            ParamType::Position => {
+                let (x, y) = s.split_once(',').ok_or_else(invalid)?;
+                Ok(Self::Position([
+                    x.trim().parse().map_err(|_| invalid())?,
+                    y.trim().parse().map_err(|_| invalid())?,
+                ]))
+            }
+            ParamType::Bool => match s {
+                "true" | "1" => Ok(Self::Bool(true)),
+                "false" | "0" => Ok(Self::Bool(false)),
+                _ => Err(invalid()),
+            },
+        }
+    }
+
+    fn to_json(self) -> serde_json::Value {
+        match self {
+            Self::Double(v) => v.into(),
+            Self::Color(v) => v.to_vec().into(),
+            Self::Position(v) => v.to_vec().into(),
+            Self::Bool(v) => v.into(),
+        }
+    }
+
+    fn write_css(self, css: &mut String, property: &str) {
+        let _ = match self {
+            Self::Double(v) => write!(css, "{property}: {v};"),
+            Self::Color(v) => write!(css, "{property}: {};", css_color(v)),
+            Self::Position([x, y]) => write!(css, "{property}-x: {x}; {property}-y: {y};"),
+            Self::Bool(v) => write!(css, "{property}: {};", u8::from(v)),
+        };
+    }
+}
+
+/// A parameter declared by the effect with `<meta name="webvfx-param" content="...">`.
+/// The content is a `;` separated list of `key=value` pairs, e.g.
+/// `name=opacity;type=double;min=0;max=1;default=0.5`.
+/// Recognized keys are `name`, `type` (`double`, `color`, `position` or `bool`),
+/// `min`, `max`, `default`, `slot` (1 based, otherwise the next free slot of the type)
+/// and `description`.
+/// The value is exposed as the `--webvfx-<name>` CSS property in addition to the slot property,
+/// so names that would shadow a built-in property (e.g. `time`, `progress` or `param1`) are rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParamDeclaration {
+    pub name: String,
+    pub param_type: ParamType,
+    /// 0 based index into the slots of `param_type`
+    pub slot: usize,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub default: ParamValue,
+    pub description: Option<String>,
+}
+
+impl ParamDeclaration {
+    /// Parse all parameter declarations in the document, assigning slots to those without one.
+    pub fn from_document(document: &BaseDocument) -> Vec<ParamDeclaration> {
+        let Ok(node_ids) = document.query_selector_all(WEBVFX_PARAM_META_SELECTOR) else {
+            return Vec::new();
+        };
+        let mut declarations: Vec<ParamDeclaration> = Vec::new();
+        let mut unassigned = Vec::new();
+        for content in node_ids.iter().filter_map(|node_id| {
+            document
+                .get_node(*node_id)
+                .and_then(|node| node.attr(local_name!("content")))
+        }) {
+            match Self::parse(content) {
+                Ok((declaration, true)) => {
+                    if declarations.iter().any(|d| {
+                        d.param_type == declaration.param_type && d.slot == declaration.slot
+                    }) {
+                        log::warn!(
+                            "param '{}' slot {} already in use, ignoring",
+                            declaration.name,
+                            declaration.slot + 1
+                        );
+                    } else {
+                        declarations.push(declaration);
+                    }
+                }
+                Ok((declaration, false)) => unassigned.push(declaration),
+                Err(e) => log::warn!("invalid param declaration '{content}': {e}"),
+            }
+        }
+        for mut declaration in unassigned {
+            let slot = (0..declaration.param_type.slot_count()).find(|slot| {
+                !declarations
+                    .iter()
+                    .any(|d| d.param_type == declaration.param_type && d.slot == *slot)
+            });
+            if let Some(slot) = slot {
+                declaration.slot = slot;
+                declarations.push(declaration);
+            } else {
+                log::warn!(
+                    "no free {} slot for param '{}', ignoring",
+                    declaration.param_type.as_str(),
+                    declaration.name
+                );
+            }
+        }
+        declarations
+    }
+
+    /// Returns the declaration and whether its slot was explicitly specified
+    fn parse(content: &str) -> anyhow::Result<(Self, bool)> {
+        let mut name = None;
+        let mut param_type = None;
+        let mut min = None;
+        let mut max = None;
+        let mut default = None;
+        let mut slot = None;
+        let mut description = None;
+        for pair in content.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("expected key=value, found '{pair}'"))?;
+            let value = value.trim();
+            match key.trim() {
+                "name" => name = Some(value),
+                "type" => param_type = Some(ParamType::parse(value)?),
+                "min" => min = Some(value.parse::<f64>()?),
+                "max" => max = Some(value.parse::<f64>()?),
+                "default" => default = Some(value),
+                "slot" => slot = Some(value.parse::<usize>()?),
+                "description" => description = Some(value.to_string()),
+                key => anyhow::bail!("unknown key '{key}'"),
+            }
+        }
+        let name = name.ok_or_else(|| anyhow::anyhow!("missing name"))?;
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            anyhow::bail!("invalid name '{name}'");
+        }
+        if is_reserved_name(name) {
+            anyhow::bail!("name '{name}' is reserved by a built-in property");
+        }
+        let param_type = param_type.unwrap_or(ParamType::Double);
+        let default = match default {
+            Some(default) => ParamValue::parse(param_type, default)?,
+            None => param_type.default_value(),
+        };
+        let explicit_slot = slot.is_some();
+        let slot = match slot {
+            Some(slot) if (1..=param_type.slot_count()).contains(&slot) => slot - 1,
+            Some(slot) => anyhow::bail!("slot {slot} out of range"),
+            None => 0,
+        };
+        let declaration = Self {
+            name: name.to_string(),
+            param_type,
+            slot,
+            min,
+            max,
+            default,
+            description,
+        };
+        Ok((declaration.clamped_default(), explicit_slot))
+    }
+
+    fn clamped_default(mut self) -> Self {
+        self.default = self.clamp(self.default);
+        self
+    }
+
+    fn clamp(&self, value: ParamValue) -> ParamValue {
+        let clamp = |v: f64| {
+            let v = self.min.map_or(v, |min| v.max(min));
+            self.max.map_or(v, |max| v.min(max))
+        };
+        match value {
+            ParamValue::Double(v) => ParamValue::Double(clamp(v)),
+            ParamValue::Position([x, y]) => ParamValue::Position([clamp(x), clamp(y)]),
+            value => value,
+        }
+    }
+
+    /// Value of this parameter, taken from its slot if set or the default otherwise
+    pub fn resolve(&self, values: &ParamValues) -> ParamValue {
+        let value = match self.param_type {
+            ParamType::Double => values.doubles[self.slot].map(ParamValue::Double),
+            ParamType::Color => values.colors[self.slot].map(ParamValue::Color),
+            ParamType::Position => values.positions[self.slot].map(ParamValue::Position),
+            ParamType::Bool => values.bools[self.slot].map(ParamValue::Bool),
+        };
+        value.map_or(self.default, |value| self.clamp(value))
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "type": self.param_type.as_str(),
+            "param": self.param_type.slot_param_name(self.slot),
+            "min": self.min,
+            "max": self.max,
+            "default": self.default.to_json(),
+            "description": self.description,
+        })
+    }
+}
+
+/// Whether the `--webvfx-<name>` property is set by WebVfx itself
+fn is_reserved_name(name: &str) -> bool {
+    let property = format!("--webvfx-{name}");
+    let is_slot = |prefix: &str| {
+        property
+            .strip_prefix(prefix)
+            .is_some_and(|slot| !slot.is_empty() && slot.bytes().all(|b| b.is_ascii_digit()))
+    };
+    [
+        WEBVFX_CSS_ANIMATION_PROPERTY,
+        WEBVFX_CSS_TIME_PROPERTY,
+        WEBVFX_CSS_PROGRESS_PROPERTY,
+        WEBVFX_CSS_FRAME_PROPERTY,
+        WEBVFX_CSS_CLIP_DURATION_PROPERTY,
+        WEBVFX_CSS_TRANSITION_PROPERTY,
+        WEBVFX_CSS_IN_DURATION_PROPERTY,
+        WEBVFX_CSS_IN_DELAY_PROPERTY,
+        WEBVFX_CSS_IN_PROGRESS_PROPERTY,
+        WEBVFX_CSS_OUT_DURATION_PROPERTY,
+        WEBVFX_CSS_OUT_DELAY_PROPERTY,
+        WEBVFX_CSS_OUT_PROGRESS_PROPERTY,
+    ]
+    .contains(&property.as_str())
+        || property.starts_with(WEBVFX_CSS_TELEMETRY_PREFIX)
+        || [
+            WEBVFX_CSS_PARAM_PREFIX,
+            WEBVFX_CSS_COLOR_PREFIX,
+            WEBVFX_CSS_POSITION_PREFIX,
+            WEBVFX_CSS_BOOL_PREFIX,
+        ]
+        .into_iter()
+        .any(is_slot)
+}
+
+/// JSON array describing the declarations, for hosts and tools
+pub fn param_declarations_json(declarations: &[ParamDeclaration]) -> String {
+    serde_json::Value::Array(declarations.iter().map(ParamDeclaration::to_json).collect())
+        .to_string()
+}
+
+fn parse_hex_color(s: &str) -> Option<[f32; 3]> {
+    let hex = s.strip_prefix('#')?;
+    let channel = |i: usize, len: usize| {
+        let c = u8::from_str_radix(hex.get(i * len..(i + 1) * len)?, 16).ok()?;
+        Some(f32::from(if len == 1 { c * 17 } else { c }) / 255.0)
+    };
+    match hex.len() {
+        3 => Some([channel(0, 1)?, channel(1, 1)?, channel(2, 1)?]),
+        6 => Some([channel(0, 2)?, channel(1, 2)?, channel(2, 2)?]),
+        _ => None,
+    }
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn css_color([r, g, b]: [f32; 3]) -> String {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("rgb({}, {}, {})", channel(r), channel(g), channel(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_declaration() {
+        let (declaration, explicit_slot) =
+            ParamDeclaration::parse("name=opacity;type=double;min=0;max=1;default=1.5").unwrap();
+        assert!(!explicit_slot);
+        assert_eq!(declaration.name, "opacity");
+        assert_eq!(declaration.param_type, ParamType::Double);
+        assert_eq!(declaration.default, ParamValue::Double(1.0));
+
+        let (declaration, explicit_slot) =
+            ParamDeclaration::parse("name=tint; type=color; default=#f80; slot=2").unwrap();
+        assert!(explicit_slot);
+        assert_eq!(declaration.slot, 1);
+        assert_eq!(
+            declaration.default,
+            ParamValue::Color([1.0, 136.0 / 255.0, 0.0])
+        );
+
+        assert!(ParamDeclaration::parse("type=double").is_err());
+        assert!(ParamDeclaration::parse("name=x;type=string").is_err());
+        assert!(ParamDeclaration::parse("name=x;slot=9").is_err());
+        for reserved in [
+            "time",
+            "progress",
+            "in-delay",
+            "param1",
+            "color4",
+            "telemetry-speed",
+        ] {
+            assert!(ParamDeclaration::parse(&format!("name={reserved}")).is_err());
+        }
+        assert!(ParamDeclaration::parse("name=timer").is_ok());
+        assert!(ParamDeclaration::parse("name=params").is_ok());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let (declaration, _) =
+            ParamDeclaration::parse("name=opacity;min=0;max=1;default=0.5").unwrap();
+        let mut values = ParamValues::default();
+        assert_eq!(declaration.resolve(&values), ParamValue::Double(0.5));
+        values.doubles[0] = Some(2.0);
+        assert_eq!(declaration.resolve(&values), ParamValue::Double(1.0));
+        let css = values.css_declarations(&[declaration]);
+        assert!(css.contains("--webvfx-opacity: 1;"));
+        assert!(css.contains("--webvfx-param1: 1;"));
+    }
+}
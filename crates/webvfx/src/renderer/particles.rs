@@ -0,0 +1,352 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+use anyrender::PaintScene;
+use blitz_dom::{BaseDocument, LocalName, Node};
+use kurbo::{Affine, Cap, Circle, Line, Point, Rect, Stroke, Vec2};
+use peniko::{
+    Color, Fill, Mix,
+    color::{Srgb, parse_color},
+};
+
+pub const WEBVFX_PARTICLES_SELECTOR: &str = ".webvfx-particles";
+/// `snow`, `sparks`, `confetti` or `bokeh`, default `snow`
+pub const WEBVFX_PARTICLES_ATTRIBUTE: &str = "data-webvfx-particles";
+/// Number of particles, default 50
+pub const WEBVFX_COUNT_ATTRIBUTE: &str = "data-webvfx-count";
+/// Integer seed, elements with different seeds have different particles
+pub const WEBVFX_SEED_ATTRIBUTE: &str = "data-webvfx-seed";
+/// CSS color of the particles, instead of the colors of the kind of particle
+pub const WEBVFX_COLOR_ATTRIBUTE: &str = "data-webvfx-color";
+
+const DEFAULT_COUNT: u32 = 50;
+const MAX_COUNT: u32 = 10_000;
+
+const CONFETTI_COLORS: [Color; 6] = [
+    Color::from_rgb8(0xef, 0x47, 0x6f),
+    Color::from_rgb8(0xff, 0xd1, 0x66),
+    Color::from_rgb8(0x06, 0xd6, 0xa0),
+    Color::from_rgb8(0x11, 0x8a, 0xb2),
+    Color::from_rgb8(0x9b, 0x5d, 0xe5),
+    Color::from_rgb8(0xf7, 0x8c, 0x6b),
+];
+const BOKEH_COLORS: [Color; 3] = [
+    Color::from_rgb8(0xff, 0xe0, 0xa0),
+    Color::from_rgb8(0xff, 0xb0, 0x70),
+    Color::from_rgb8(0xff, 0xf4, 0xe0),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Snow,
+    Sparks,
+    Confetti,
+    Bokeh,
+}
+
+#[derive(Debug)]
+struct Emitter {
+    kind: Kind,
+    count: u32,
+    seed: u64,
+    color: Option<Color>,
+}
+
+/// A particle at a point in time, in the coordinates of its element
+#[derive(Debug)]
+struct Particle {
+    center: Point,
+    /// Radius, or stroke width of sparks
+    size: f64,
+    angle: f64,
+    /// Horizontal scale of confetti as it flips
+    flip: f64,
+    /// Offset from the tail of a spark to its center
+    trail: Vec2,
+    color: Color,
+}
+
+/// Particles painted over `.webvfx-particles` elements, clipped to the element box.
+/// The kind of particle is set with `data-webvfx-particles`, the number with
+/// `data-webvfx-count` and the seed with `data-webvfx-seed`.
+///
+/// Particles are respawned continuously and each is a function of the time and seed only,
+/// so any frame can be rendered without rendering the frames before it. They are painted
+/// above the document content, ignoring the transform and opacity of the element.
+#[derive(Default)]
+pub struct Particles {
+    emitters: Vec<(usize, Emitter)>,
+}
+
+impl Particles {
+    pub fn new(document: &BaseDocument) -> Self {
+        let mut particles = Self::default();
+        particles.bind(document);
+        particles
+    }
+
+    /// Find the particle elements in a new document
+    pub fn bind(&mut self, document: &BaseDocument) {
+        self.emitters = document
+            .query_selector_all(WEBVFX_PARTICLES_SELECTOR)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|node_id| {
+                let node = document.get_node(*node_id)?;
+                match Emitter::from_node(node) {
+                    Ok(emitter) => Some((*node_id, emitter)),
+                    Err(e) => {
+                        log::warn!("invalid particles: {e}");
+                        None
+                    }
+                }
+            })
+            .collect();
+    }
+
+    /// Paint the particles at `time` using the layout of `document`
+    pub fn paint(&self, time: f64, document: &BaseDocument, scene: &mut impl PaintScene) {
+        for (node_id, emitter) in &self.emitters {
+            let Some(node) = document.get_node(*node_id) else {
+                continue;
+            };
+            let size = node.final_layout.size;
+            if size.width <= 0.0 || size.height <= 0.0 {
+                continue;
+            }
+            let position = node.absolute_position(0.0, 0.0);
+            let transform = Affine::translate((f64::from(position.x), f64::from(position.y)));
+            let bounds = Rect::new(0.0, 0.0, f64::from(size.width), f64::from(size.height));
+            scene.push_layer(Mix::Normal, 1.0, transform, &bounds);
+            for index in 0..emitter.count {
+                emitter.paint_particle(
+                    &emitter.particle(index, time, bounds.width(), bounds.height()),
+                    transform,
+                    scene,
+                );
+            }
+            scene.pop_layer();
+        }
+    }
+}
+
+impl Emitter {
+    fn from_node(node: &Node) -> anyhow::Result<Self> {
+        let attr = |name: &str| {
+            node.attr(LocalName::from(name))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+        let kind = match attr(WEBVFX_PARTICLES_ATTRIBUTE).unwrap_or("snow") {
+            "snow" => Kind::Snow,
+            "sparks" => Kind::Sparks,
+            "confetti" => Kind::Confetti,
+            "bokeh" => Kind::Bokeh,
+            kind => return Err(anyhow::anyhow!("kind '{kind}'")),
+        };
+        let count = attr(WEBVFX_COUNT_ATTRIBUTE)
+            .map(|count| {
+                count
+                    .parse::<u32>()
+                    .map_err(|_| anyhow::anyhow!("count '{count}'"))
+            })
+            .transpose()?
+            .unwrap_or(DEFAULT_COUNT)
+            .min(MAX_COUNT);
+        let seed = attr(WEBVFX_SEED_ATTRIBUTE)
+            .map(|seed| {
+                seed.parse::<u64>()
+                    .map_err(|_| anyhow::anyhow!("seed '{seed}'"))
+            })
+            .transpose()?
+            .unwrap_or(0);
+        let color = attr(WEBVFX_COLOR_ATTRIBUTE)
+            .map(|color| {
+                parse_color(color)
+                    .map(|color| color.to_alpha_color::<Srgb>())
+                    .map_err(|e| anyhow::anyhow!("color '{color}': {e}"))
+            })
+            .transpose()?;
+        Ok(Self {
+            kind,
+            count,
+            seed,
+            color,
+        })
+    }
+
+    /// The state of particle `index` at `time` in an element `width` by `height`
+    #[allow(clippy::cast_precision_loss, clippy::cast_sign_loss)]
+    fn particle(&self, index: u32, time: f64, width: f64, height: f64) -> Particle {
+        // Properties fixed for all lives of the particle
+        let fixed = |property| random(self.seed, index, u64::MAX, property);
+        // Age of the particle in its current life, and a random source for that life.
+        // Lives are offset so particles are already spread out at time 0.
+        let lifetime = |life: f64| {
+            let elapsed = time.max(0.0) + fixed(0) * life;
+            let cycle = (elapsed / life).floor();
+            let seed = self.seed;
+            (elapsed - cycle * life, move |property| {
+                random(seed, index, cycle.to_bits(), property)
+            })
+        };
+        let mut particle = Particle {
+            center: Point::ORIGIN,
+            size: 0.0,
+            angle: 0.0,
+            flip: 1.0,
+            trail: Vec2::ZERO,
+            color: Color::WHITE,
+        };
+        match self.kind {
+            Kind::Snow => {
+                let speed = 30.0 + 50.0 * fixed(1);
+                particle.size = 1.5 + 2.5 * fixed(2);
+                let (age, random) = lifetime((height + 2.0 * particle.size) / speed);
+                let sway = (5.0 + 10.0 * random(2))
+                    * ((0.2 + 0.3 * random(3)) * age * TAU + random(4) * TAU).sin();
+                particle.center = Point::new(random(1) * width + sway, speed * age - particle.size);
+                particle.color = Color::WHITE.multiply_alpha(0.6 + 0.4 * random(5) as f32);
+            }
+            Kind::Sparks => {
+                let life = 0.6 + fixed(1);
+                let (age, random) = lifetime(life);
+                let progress = age / life;
+                let angle = -FRAC_PI_2 + (random(1) - 0.5) * FRAC_PI_2;
+                let speed = height * (0.8 + 0.8 * random(2));
+                let gravity = height * 1.5;
+                let (sin, cos) = angle.sin_cos();
+                particle.center = Point::new(
+                    width / 2.0 + cos * speed * age,
+                    height + sin * speed * age + 0.5 * gravity * age * age,
+                );
+                particle.trail = Vec2::new(cos * speed, sin * speed + gravity * age) * 0.03;
+                particle.size = 0.5 + 1.5 * (1.0 - progress);
+                let fade = progress as f32;
+                particle.color = Color::new([1.0, 0.9 - 0.5 * fade, 0.5 - 0.4 * fade, 1.0 - fade]);
+            }
+            Kind::Confetti => {
+                let speed = 60.0 + 60.0 * fixed(1);
+                particle.size = 4.0 + 4.0 * fixed(2);
+                let (age, random) = lifetime((height + 4.0 * particle.size) / speed);
+                let sway = 20.0
+                    * random(2)
+                    * ((0.3 + 0.4 * random(3)) * age * TAU + random(4) * TAU).sin();
+                particle.center =
+                    Point::new(random(1) * width + sway, speed * age - 2.0 * particle.size);
+                particle.angle = random(5) * TAU + (random(6) - 0.5) * 12.0 * age;
+                particle.flip = ((1.0 + 2.0 * random(7)) * age * TAU).cos();
+                particle.color =
+                    CONFETTI_COLORS[(random(8) * CONFETTI_COLORS.len() as f64) as usize];
+            }
+            Kind::Bokeh => {
+                let life = 3.0 + 3.0 * fixed(1);
+                let (age, random) = lifetime(life);
+                particle.center = Point::new(
+                    random(1) * width + (random(2) - 0.5) * 20.0 * age,
+                    random(3) * height + (random(4) - 0.5) * 20.0 * age,
+                );
+                particle.size = 10.0 + 30.0 * random(5);
+                particle.color = BOKEH_COLORS[(random(6) * BOKEH_COLORS.len() as f64) as usize]
+                    .multiply_alpha(0.35 * (PI * age / life).sin() as f32);
+            }
+        }
+        if let Some(color) = self.color {
+            particle.color = color.multiply_alpha(particle.color.components[3]);
+        }
+        particle
+    }
+
+    fn paint_particle(&self, particle: &Particle, transform: Affine, scene: &mut impl PaintScene) {
+        match self.kind {
+            Kind::Snow | Kind::Bokeh => scene.fill(
+                Fill::NonZero,
+                transform,
+                particle.color,
+                None,
+                &Circle::new(particle.center, particle.size),
+            ),
+            Kind::Sparks => scene.stroke(
+                &Stroke::new(particle.size).with_caps(Cap::Round),
+                transform,
+                particle.color,
+                None,
+                &Line::new(particle.center - particle.trail, particle.center),
+            ),
+            Kind::Confetti => {
+                let half = particle.size / 2.0;
+                scene.fill(
+                    Fill::NonZero,
+                    transform
+                        * Affine::translate(particle.center.to_vec2())
+                        * Affine::rotate(particle.angle)
+                        * Affine::scale_non_uniform(particle.flip, 1.0),
+                    particle.color,
+                    None,
+                    &Rect::new(-half, -1.6 * half, half, 1.6 * half),
+                );
+            }
+        }
+    }
+}
+
+/// Random number in `[0, 1)` for a property of a particle in one of its lives,
+/// from the `splitmix64` finalizer
+fn random(seed: u64, index: u32, cycle: u64, property: u64) -> f64 {
+    let mut x = seed
+        ^ (u64::from(index) << 32)
+        ^ cycle.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        ^ property.wrapping_mul(0xd6e8_feb8_6659_fd93);
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+    // 52 random mantissa bits of a float in [1, 2)
+    f64::from_bits(0x3ff0_0000_0000_0000 | (x >> 12)) - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emitter(kind: Kind, seed: u64) -> Emitter {
+        Emitter {
+            kind,
+            count: 20,
+            seed,
+            color: None,
+        }
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let centers = |emitter: &Emitter, time: f64| -> Vec<Point> {
+            (0..emitter.count)
+                .map(|index| emitter.particle(index, time, 320.0, 240.0).center)
+                .collect()
+        };
+        let direct = centers(&emitter(Kind::Snow, 1), 2.5);
+        // Evaluating other times first, in any order, does not change the result
+        let seeked = emitter(Kind::Snow, 1);
+        for time in [5.0, 0.0, 3.75, 2.46] {
+            assert_ne!(centers(&seeked, time), direct, "{time}");
+        }
+        assert_eq!(centers(&seeked, 2.5), direct);
+        for center in &direct {
+            assert!((-30.0..=350.0).contains(&center.x), "{center:?}");
+            assert!((-10.0..=250.0).contains(&center.y), "{center:?}");
+        }
+        assert_ne!(centers(&emitter(Kind::Snow, 2), 2.5), direct);
+    }
+
+    #[test]
+    fn test_random() {
+        for property in 0..1000 {
+            let value = random(7, 3, 0, property);
+            assert!((0.0..1.0).contains(&value));
+        }
+        assert_ne!(random(7, 3, 0, 0), random(7, 3, 1, 0));
+    }
+}
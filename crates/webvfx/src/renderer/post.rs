@@ -0,0 +1,154 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use blitz_dom::{BaseDocument, local_name};
+
+use super::imaging;
+
+pub const WEBVFX_POST_META_SELECTOR: &str = r#"meta[name="webvfx-post"]"#;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PostOp {
+    /// Noise of up to `amount` (0..1), different every frame
+    Grain { amount: f32 },
+    /// Darken the corners by `amount`, starting at `radius` (0 center, 1 corners)
+    Vignette { amount: f32, radius: f32 },
+    /// Gaussian blur, `radius` is the standard deviation in pixels
+    Blur { radius: f32 },
+    /// Unsharp mask
+    Sharpen { amount: f32, radius: f32 },
+}
+
+/// Operations applied in order to each rendered frame, e.g.
+/// `vignette(0.4) grain(0.05)`. Declared by the effect with
+/// `<meta name="webvfx-post" content="...">` or set with the `post` plugin param.
+///
+/// - `grain(amount)`, default 0.08
+/// - `vignette(amount, radius)`, default 0.5, 0.5
+/// - `blur(radius)`, in pixels, default 2
+/// - `sharpen(amount, radius)`, default 1, 1px
+///
+/// Arguments are numbers, percentages or pixel lengths.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PostChain {
+    ops: Vec<PostOp>,
+}
+
+impl PostChain {
+    #[allow(clippy::missing_errors_doc)]
+    pub fn parse(chain: &str) -> anyhow::Result<Self> {
+        let mut ops = Vec::new();
+        let mut rest = chain.trim();
+        while !rest.is_empty() {
+            let (function, remainder) = rest
+                .split_once(')')
+                .ok_or_else(|| anyhow::anyhow!("missing ')' in '{rest}'"))?;
+            let (name, args) = function
+                .split_once('(')
+                .ok_or_else(|| anyhow::anyhow!("missing '(' in '{function}'"))?;
+            let name = name.trim();
+            let args = args
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| parse_arg(arg).ok_or_else(|| anyhow::anyhow!("{name} '{arg}'")))
+                .collect::<anyhow::Result<Vec<f32>>>()?;
+            let arg = |i: usize, default: f32| args.get(i).copied().unwrap_or(default);
+            let (op, arg_count) = match name {
+                "grain" => (
+                    PostOp::Grain {
+                        amount: arg(0, 0.08),
+                    },
+                    1,
+                ),
+                "vignette" => (
+                    PostOp::Vignette {
+                        amount: arg(0, 0.5),
+                        radius: arg(1, 0.5),
+                    },
+                    2,
+                ),
+                "blur" => (
+                    PostOp::Blur {
+                        radius: arg(0, 2.0),
+                    },
+                    1,
+                ),
+                "sharpen" => (
+                    PostOp::Sharpen {
+                        amount: arg(0, 1.0),
+                        radius: arg(1, 1.0),
+                    },
+                    2,
+                ),
+                _ => return Err(anyhow::anyhow!("unknown operation '{name}'")),
+            };
+            if args.len() > arg_count {
+                return Err(anyhow::anyhow!("too many arguments to {name}"));
+            }
+            ops.push(op);
+            rest = remainder.trim_start();
+        }
+        Ok(Self { ops })
+    }
+
+    /// The chain declared by the document, if any
+    pub fn from_document(document: &BaseDocument) -> Option<anyhow::Result<Self>> {
+        let node_id = *document
+            .query_selector_all(WEBVFX_POST_META_SELECTOR)
+            .ok()?
+            .first()?;
+        let content = document.get_node(node_id)?.attr(local_name!("content"))?;
+        Some(Self::parse(content))
+    }
+
+    /// Apply the operations to a frame rendered at `time`, which seeds the grain
+    pub fn apply(&self, time: f64, frame: &mut [u8], width: u32, height: u32) {
+        let (width, height) = (width as usize, height as usize);
+        for op in &self.ops {
+            match *op {
+                PostOp::Grain { amount } => imaging::grain(frame, amount, time.to_bits()),
+                PostOp::Vignette { amount, radius } => {
+                    imaging::vignette(frame, width, height, amount, radius);
+                }
+                PostOp::Blur { radius } => imaging::gaussian_blur(frame, width, height, radius),
+                PostOp::Sharpen { amount, radius } => {
+                    imaging::unsharp_mask(frame, width, height, radius, amount);
+                }
+            }
+        }
+    }
+}
+
+fn parse_arg(arg: &str) -> Option<f32> {
+    if let Some(percent) = arg.strip_suffix('%') {
+        percent.parse::<f32>().ok().map(|percent| percent / 100.0)
+    } else {
+        arg.strip_suffix("px").unwrap_or(arg).parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let chain = PostChain::parse("vignette(40%, 0.6) grain() blur(3px)").unwrap();
+        assert_eq!(
+            chain.ops,
+            vec![
+                PostOp::Vignette {
+                    amount: 0.4,
+                    radius: 0.6
+                },
+                PostOp::Grain { amount: 0.08 },
+                PostOp::Blur { radius: 3.0 },
+            ]
+        );
+        assert_eq!(PostChain::parse("  ").unwrap(), PostChain::default());
+        assert!(PostChain::parse("glow(1)").is_err());
+        assert!(PostChain::parse("blur(1, 2)").is_err());
+        assert!(PostChain::parse("blur(x)").is_err());
+        assert!(PostChain::parse("blur(1").is_err());
+    }
+}
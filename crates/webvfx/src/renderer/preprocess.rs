@@ -0,0 +1,349 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::sync::Arc;
+
+use blitz_dom::{LocalName, Node};
+use peniko::color::{Srgb, parse_color};
+
+use super::{
+    imaging::{self, ColorMatrix},
+    lut::{Interpolation, Lut3d, WEBVFX_LUT_ATTRIBUTE, WEBVFX_LUT_INTERPOLATION_ATTRIBUTE},
+};
+
+/// Region of the input `x y width height`, in pixels or percentages of the input size
+pub const WEBVFX_CROP_ATTRIBUTE: &str = "data-webvfx-crop";
+/// `green`, `blue` or a CSS color made transparent
+pub const WEBVFX_CHROMA_KEY_ATTRIBUTE: &str = "data-webvfx-chroma-key";
+/// Chroma distance from the key below which pixels are transparent, default 0.4
+pub const WEBVFX_CHROMA_THRESHOLD_ATTRIBUTE: &str = "data-webvfx-chroma-threshold";
+/// Chroma distance over which keyed pixels fade to opaque, default 0.1
+pub const WEBVFX_CHROMA_SOFTNESS_ATTRIBUTE: &str = "data-webvfx-chroma-softness";
+/// Spill suppression from 0 to 1, default 1
+pub const WEBVFX_CHROMA_SPILL_ATTRIBUTE: &str = "data-webvfx-chroma-spill";
+pub const WEBVFX_BRIGHTNESS_ATTRIBUTE: &str = "data-webvfx-brightness";
+pub const WEBVFX_CONTRAST_ATTRIBUTE: &str = "data-webvfx-contrast";
+pub const WEBVFX_SATURATION_ATTRIBUTE: &str = "data-webvfx-saturation";
+/// Gaussian blur `4px`, or box blur `box 4px`
+pub const WEBVFX_BLUR_ATTRIBUTE: &str = "data-webvfx-blur";
+
+const PREPROCESS_ATTRIBUTES: [&str; 7] = [
+    WEBVFX_CROP_ATTRIBUTE,
+    WEBVFX_CHROMA_KEY_ATTRIBUTE,
+    WEBVFX_BRIGHTNESS_ATTRIBUTE,
+    WEBVFX_CONTRAST_ATTRIBUTE,
+    WEBVFX_SATURATION_ATTRIBUTE,
+    WEBVFX_LUT_ATTRIBUTE,
+    WEBVFX_BLUR_ATTRIBUTE,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Crop {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ChromaKey {
+    key: [f32; 3],
+    threshold: f32,
+    softness: f32,
+    spill: f32,
+}
+
+/// A LUT linked with `data-webvfx-lut`, loaded by the renderer
+#[derive(Debug, PartialEq)]
+struct InputLut {
+    href: String,
+    interpolation: Interpolation,
+    lut: Option<Arc<Lut3d>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Blur {
+    Gaussian(f32),
+    Box(usize),
+}
+
+/// Processing of an input frame before it is shown by its `img.webvfx-videoN` elements,
+/// declared with attributes on the element: crop, chroma key, brightness/contrast/saturation
+/// (with the CSS filter function values), a `.cube` LUT and blur, applied in that order.
+/// The attributes of the first element of an input apply to all its elements.
+///
+/// ```html
+/// <img class="webvfx-video2" data-webvfx-chroma-key="green" data-webvfx-crop="10% 0 80% 100%">
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Preprocess {
+    input_width: usize,
+    crop: Crop,
+    chroma_key: Option<ChromaKey>,
+    color: Option<ColorMatrix>,
+    lut: Option<InputLut>,
+    blur: Option<Blur>,
+}
+
+impl Preprocess {
+    /// The processing declared by `node` for `width` by `height` input frames,
+    /// `None` if it declares none
+    #[allow(clippy::cast_sign_loss)]
+    pub fn from_node(node: &Node, width: u32, height: u32) -> anyhow::Result<Option<Self>> {
+        let attr = |name: &str| {
+            node.attr(LocalName::from(name))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+        if PREPROCESS_ATTRIBUTES
+            .iter()
+            .all(|name| attr(name).is_none())
+        {
+            return Ok(None);
+        }
+        let number = |name: &str, default: f32| {
+            attr(name)
+                .map(|value| parse_number(value).ok_or_else(|| anyhow::anyhow!("{name} '{value}'")))
+                .transpose()
+                .map(|value| value.unwrap_or(default))
+        };
+        let (width, height) = (width as usize, height as usize);
+
+        let crop = attr(WEBVFX_CROP_ATTRIBUTE)
+            .map(|crop| parse_crop(crop, width, height))
+            .transpose()?
+            .unwrap_or(Crop {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            });
+
+        let chroma_key = attr(WEBVFX_CHROMA_KEY_ATTRIBUTE)
+            .map(|key| -> anyhow::Result<ChromaKey> {
+                let key = match key {
+                    "green" => [0.0, 1.0, 0.0],
+                    "blue" => [0.0, 0.0, 1.0],
+                    key => {
+                        let [r, g, b, _] = parse_color(key)
+                            .map_err(|e| anyhow::anyhow!("chroma key '{key}': {e}"))?
+                            .to_alpha_color::<Srgb>()
+                            .components;
+                        [r, g, b]
+                    }
+                };
+                Ok(ChromaKey {
+                    key,
+                    threshold: number(WEBVFX_CHROMA_THRESHOLD_ATTRIBUTE, 0.4)?,
+                    softness: number(WEBVFX_CHROMA_SOFTNESS_ATTRIBUTE, 0.1)?,
+                    spill: number(WEBVFX_CHROMA_SPILL_ATTRIBUTE, 1.0)?,
+                })
+            })
+            .transpose()?;
+
+        let color = ColorMatrix::brightness(number(WEBVFX_BRIGHTNESS_ATTRIBUTE, 1.0)?)
+            .then(&ColorMatrix::contrast(number(
+                WEBVFX_CONTRAST_ATTRIBUTE,
+                1.0,
+            )?))
+            .then(&ColorMatrix::saturate(number(
+                WEBVFX_SATURATION_ATTRIBUTE,
+                1.0,
+            )?));
+
+        let lut = attr(WEBVFX_LUT_ATTRIBUTE)
+            .map(|href| -> anyhow::Result<InputLut> {
+                Ok(InputLut {
+                    href: href.to_string(),
+                    interpolation: Interpolation::parse(
+                        attr(WEBVFX_LUT_INTERPOLATION_ATTRIBUTE).unwrap_or_default(),
+                    )?,
+                    lut: None,
+                })
+            })
+            .transpose()?;
+
+        let blur = attr(WEBVFX_BLUR_ATTRIBUTE)
+            .map(|blur| {
+                let invalid = || anyhow::anyhow!("blur '{blur}'");
+                match blur.strip_prefix("box") {
+                    Some(radius) => parse_length(radius.trim())
+                        .filter(|radius| *radius >= 0.0)
+                        .map(|radius| Blur::Box(radius.round() as usize))
+                        .ok_or_else(invalid),
+                    None => parse_length(blur).map(Blur::Gaussian).ok_or_else(invalid),
+                }
+            })
+            .transpose()?;
+
+        Ok(Some(Self {
+            input_width: width,
+            crop,
+            chroma_key,
+            color: (color != ColorMatrix::IDENTITY).then_some(color),
+            lut,
+            blur,
+        }))
+    }
+
+    /// Size of the processed frames
+    pub fn output_size(&self) -> (u32, u32) {
+        (self.crop.width as u32, self.crop.height as u32)
+    }
+
+    /// The `data-webvfx-lut` URL, relative to the document
+    pub fn lut_href(&self) -> Option<&str> {
+        self.lut.as_ref().map(|lut| lut.href.as_str())
+    }
+
+    /// Set the LUT loaded from `lut_href`, `None` if it failed to load
+    pub fn set_lut(&mut self, lut: Option<Arc<Lut3d>>) {
+        if let Some(input_lut) = &mut self.lut {
+            input_lut.lut = lut;
+        }
+    }
+
+    /// Process an `input` frame into `output`, which is `output_size`
+    pub fn apply(&self, input: &[u8], output: &mut [u8]) {
+        let row_length = self.crop.width * 4;
+        for (y, row) in output.chunks_exact_mut(row_length).enumerate() {
+            let start = ((self.crop.y + y) * self.input_width + self.crop.x) * 4;
+            row.copy_from_slice(&input[start..start + row_length]);
+        }
+        if let Some(ChromaKey {
+            key,
+            threshold,
+            softness,
+            spill,
+        }) = self.chroma_key
+        {
+            imaging::chroma_key(output, key, threshold, softness, spill);
+        }
+        if let Some(color) = &self.color {
+            color.apply(output);
+        }
+        if let Some(InputLut {
+            interpolation,
+            lut: Some(lut),
+            ..
+        }) = &self.lut
+        {
+            lut.apply(output, *interpolation);
+        }
+        match self.blur {
+            Some(Blur::Gaussian(sigma)) => {
+                imaging::gaussian_blur(output, self.crop.width, self.crop.height, sigma);
+            }
+            Some(Blur::Box(radius)) => {
+                imaging::box_blur(output, self.crop.width, self.crop.height, radius);
+            }
+            None => {}
+        }
+    }
+}
+
+/// A number or percentage, e.g. `1.2` or `120%`
+fn parse_number(value: &str) -> Option<f32> {
+    match value.strip_suffix('%') {
+        Some(percent) => percent
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .map(|percent| percent / 100.0),
+        None => value.parse().ok(),
+    }
+}
+
+/// A number of pixels, with an optional `px` suffix
+fn parse_length(value: &str) -> Option<f32> {
+    value
+        .strip_suffix("px")
+        .unwrap_or(value)
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Parse `x y width height` and clamp it to the input frame
+#[allow(clippy::cast_precision_loss, clippy::cast_sign_loss)]
+fn parse_crop(crop: &str, width: usize, height: usize) -> anyhow::Result<Crop> {
+    let invalid = || anyhow::anyhow!("crop '{crop}'");
+    let values: Vec<&str> = crop
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .collect();
+    if values.len() != 4 {
+        return Err(invalid());
+    }
+    let values = values
+        .into_iter()
+        .zip([width, height, width, height])
+        .map(|(value, size)| {
+            let pixels = match value.strip_suffix('%') {
+                Some(percent) => percent.parse::<f32>().ok()? / 100.0 * size as f32,
+                None => parse_length(value)?,
+            };
+            Some(pixels.round().clamp(0.0, size as f32) as usize)
+        })
+        .collect::<Option<Vec<usize>>>()
+        .ok_or_else(invalid)?;
+    let [x, y, crop_width, crop_height] = values[..] else {
+        return Err(invalid());
+    };
+    let x = x.min(width - 1);
+    let y = y.min(height - 1);
+    Ok(Crop {
+        x,
+        y,
+        width: crop_width.clamp(1, width - x),
+        height: crop_height.clamp(1, height - y),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_crop() {
+        assert_eq!(
+            parse_crop("10% 0 80% 100%", 320, 240).unwrap(),
+            Crop {
+                x: 32,
+                y: 0,
+                width: 256,
+                height: 240
+            }
+        );
+        assert_eq!(
+            parse_crop("300px, 200px, 100px, 100px", 320, 240).unwrap(),
+            Crop {
+                x: 300,
+                y: 200,
+                width: 20,
+                height: 40
+            }
+        );
+        assert!(parse_crop("0 0 100", 320, 240).is_err());
+        assert!(parse_crop("0 0 100 100 5", 320, 240).is_err());
+    }
+
+    #[test]
+    fn test_apply_crop() {
+        // 3x2 input, each pixel's red channel is its index
+        let input: Vec<u8> = (0..6).flat_map(|i| [i, 0, 0, 255]).collect();
+        let preprocess = Preprocess {
+            input_width: 3,
+            crop: parse_crop("1 0 2 2", 3, 2).unwrap(),
+            chroma_key: None,
+            color: None,
+            lut: None,
+            blur: None,
+        };
+        assert_eq!(preprocess.output_size(), (2, 2));
+        let mut output = vec![0; 2 * 2 * 4];
+        preprocess.apply(&input, &mut output);
+        let red: Vec<u8> = output.iter().step_by(4).copied().collect();
+        assert_eq!(red, vec![1, 2, 4, 5]);
+    }
+}
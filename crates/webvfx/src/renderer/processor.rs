@@ -2,17 +2,30 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use std::{
-    path::{self, Path},
+    path::{self, Path, PathBuf},
     sync::mpsc::{Receiver, Sender, channel},
     thread::{self, JoinHandle},
 };
 
-use super::WebVfxRenderer;
+use super::{
+    WebVfxRenderer,
+    lut::Lut3d,
+    params::{ParamDeclaration, ParamValues},
+    post::PostChain,
+    subtitles::Subtitles,
+    telemetry::Telemetry,
+    timeline::Timeline,
+    timing::TimeParams,
+    watch::FileWatcher,
+};
 use anyhow::Context;
 use blitz_traits::net::Url;
 
 struct RenderJob<const S: usize> {
     time: f64,
+    params: ParamValues,
+    time_params: TimeParams,
+    post: Option<PostChain>,
     inputs: [(*const u8, usize); S],
     output: (*mut u8, usize),
 }
@@ -23,7 +36,14 @@ struct RenderJob<const S: usize> {
 unsafe impl<const S: usize> Send for RenderJob<S> {}
 
 impl<const S: usize> RenderJob<S> {
-    fn new(time: f64, inputs: [&[u32]; S], output: &mut [u32]) -> Self {
+    fn new(
+        time: f64,
+        params: &ParamValues,
+        time_params: &TimeParams,
+        post: Option<&PostChain>,
+        inputs: [&[u32]; S],
+        output: &mut [u32],
+    ) -> Self {
         let inputs: [(*const u8, usize); S] = inputs
             .into_iter()
             .map(|input| (input.as_ptr().cast::<u8>(), size_of_val(input)))
@@ -32,36 +52,272 @@ impl<const S: usize> RenderJob<S> {
             .unwrap();
         Self {
             time,
+            params: params.clone(),
+            time_params: time_params.clone(),
+            post: post.cloned(),
             inputs,
             output: (output.as_mut_ptr().cast::<u8>(), size_of_val(output)),
         }
     }
 }
 
+enum Html {
+    Path(PathBuf),
+    Source {
+        html: String,
+        base_url: Option<String>,
+    },
+}
+
+/// Files overriding those linked from the HTML
+#[derive(Clone, Debug, Default)]
+pub struct ResourcePaths {
+    /// Keyframe timeline JSON, see `Timeline`
+    pub timeline: Option<PathBuf>,
+    /// SRT or WebVTT subtitles shown by all `.webvfx-subtitle` elements
+    pub subtitles: Option<PathBuf>,
+    /// CSV or GPX telemetry, see `Telemetry`
+    pub telemetry: Option<PathBuf>,
+    /// `.cube` LUT applied to the output
+    pub lut: Option<PathBuf>,
+}
+
+/// Everything the document is loaded from, so it can be reloaded when files change
+struct DocumentSource {
+    html: Html,
+    template_data: Option<TemplateData>,
+    resources: ResourcePaths,
+}
+
+/// A loaded document, ready to create a renderer from on the worker thread
+struct LoadedDocument {
+    url: Url,
+    html: String,
+    timeline: Option<Timeline>,
+    subtitles: Option<Subtitles>,
+    telemetry: Option<Telemetry>,
+    lut: Option<Lut3d>,
+    /// Template data for scripts
+    #[cfg(feature = "scripting")]
+    template_json: Option<serde_json::Value>,
+}
+
+impl LoadedDocument {
+    fn create_renderer<const S: usize>(
+        self,
+        animation_duration: &str,
+        width: u32,
+        height: u32,
+    ) -> WebVfxRenderer<S> {
+        let mut renderer =
+            WebVfxRenderer::<S>::new(&self.url, &self.html, animation_duration, width, height);
+        if let Some(timeline) = self.timeline {
+            renderer.set_timeline(timeline);
+        }
+        if let Some(subtitles) = self.subtitles {
+            renderer.set_subtitles(subtitles);
+        }
+        if let Some(telemetry) = self.telemetry {
+            renderer.set_telemetry(telemetry);
+        }
+        if let Some(lut) = self.lut {
+            renderer.set_lut(lut);
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(template_json) = &self.template_json {
+            renderer.set_script_data(template_json);
+        }
+        renderer
+    }
+}
+
+impl DocumentSource {
+    fn load(&self) -> anyhow::Result<LoadedDocument> {
+        let (url, html) = match &self.html {
+            Html::Path(html_path) => process_template(html_path, self.template_data.as_ref())?,
+            Html::Source { html, base_url } => {
+                process_template_source(html, base_url.as_deref(), self.template_data.as_ref())?
+            }
+        };
+        Ok(LoadedDocument {
+            url,
+            html,
+            timeline: load_resource(
+                self.resources.timeline.as_deref(),
+                "timeline",
+                Timeline::parse,
+            )?,
+            subtitles: load_resource(self.resources.subtitles.as_deref(), "subtitle", |data| {
+                Subtitles::parse(&String::from_utf8_lossy(data))
+            })?,
+            telemetry: load_resource(
+                self.resources.telemetry.as_deref(),
+                "telemetry",
+                Telemetry::parse,
+            )?,
+            lut: load_resource(self.resources.lut.as_deref(), "LUT", Lut3d::parse)?,
+            #[cfg(feature = "scripting")]
+            template_json: self
+                .template_data
+                .as_ref()
+                .map(TemplateData::read)
+                .transpose()?,
+        })
+    }
+
+    fn watch(&self, watcher: &mut FileWatcher) {
+        if let Html::Path(html_path) = &self.html
+            && let Ok(html_path) = path::absolute(html_path)
+        {
+            watcher.watch(html_path);
+        }
+        if let Some(TemplateData::Path(json_path)) = &self.template_data {
+            watcher.watch(json_path);
+        }
+        for path in [
+            &self.resources.timeline,
+            &self.resources.subtitles,
+            &self.resources.telemetry,
+            &self.resources.lut,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            watcher.watch(path);
+        }
+    }
+}
+
+/// Read and parse a resource file, `kind` names it in errors
+fn load_resource<T>(
+    path: Option<&Path>,
+    kind: &str,
+    parse: impl FnOnce(&[u8]) -> anyhow::Result<T>,
+) -> anyhow::Result<Option<T>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let data = std::fs::read(path)
+        .with_context(|| format!("WebVfx: failed to read {kind} path '{}'", path.display()))?;
+    parse(&data)
+        .with_context(|| format!("WebVfx: failed to parse {kind} path '{}'", path.display()))
+        .map(Some)
+}
+
+/// Information about the loaded document
+#[derive(Clone, Debug, Default)]
+pub struct DocumentInfo {
+    pub param_declarations: Vec<ParamDeclaration>,
+    /// Whether an `img.webvfx-videoN` element was found for each input
+    pub video_inputs: Vec<bool>,
+}
+
+impl DocumentInfo {
+    fn new<const S: usize>(renderer: &WebVfxRenderer<S>) -> Self {
+        Self {
+            param_declarations: renderer.param_declarations().to_vec(),
+            video_inputs: renderer.video_inputs(),
+        }
+    }
+}
+
+/// Outcome of rendering a frame
+#[derive(Debug, Default)]
+pub struct FrameResult {
+    /// Set if the document was reloaded before rendering
+    pub reloaded: Option<DocumentInfo>,
+    /// Errors that did not prevent rendering, e.g. failed fetches or reloads
+    pub errors: Vec<String>,
+}
+
 pub struct RenderProcessor<const S: usize> {
     job_tx: Option<Sender<RenderJob<S>>>,
-    job_done_rx: Receiver<()>,
+    job_done_rx: Receiver<FrameResult>,
     worker: Option<JoinHandle<()>>,
+    document_info: DocumentInfo,
 }
 
 impl<const S: usize> RenderProcessor<S> {
     pub fn new(
         html_path: impl AsRef<Path>,
-        json_path: Option<impl AsRef<Path>>,
+        template_data: Option<TemplateData>,
+        resources: ResourcePaths,
         animation_duration: &str,
         width: u32,
         height: u32,
     ) -> anyhow::Result<Self> {
-        let (url, html) = process_template(html_path, json_path)?;
+        let source = DocumentSource {
+            html: Html::Path(html_path.as_ref().to_path_buf()),
+            template_data,
+            resources,
+        };
+        Self::spawn(source, animation_duration, width, height)
+    }
+
+    /// Create a processor from inline HTML markup instead of a file.
+    /// Relative URLs in the HTML are resolved against `base_url`, see `process_template_source`.
+    pub fn new_with_source(
+        html_source: &str,
+        base_url: Option<&str>,
+        template_data: Option<TemplateData>,
+        resources: ResourcePaths,
+        animation_duration: &str,
+        width: u32,
+        height: u32,
+    ) -> anyhow::Result<Self> {
+        let source = DocumentSource {
+            html: Html::Source {
+                html: html_source.to_string(),
+                base_url: base_url.map(String::from),
+            },
+            template_data,
+            resources,
+        };
+        Self::spawn(source, animation_duration, width, height)
+    }
+
+    /// The document is reloaded if the HTML, the JSON, the timeline, the subtitles, the telemetry or any local file it fetched changes
+    fn spawn(
+        source: DocumentSource,
+        animation_duration: &str,
+        width: u32,
+        height: u32,
+    ) -> anyhow::Result<Self> {
+        let document = source.load()?;
 
         let (job_tx, job_rx) = channel::<RenderJob<S>>();
-        let (job_done_tx, job_done_rx) = channel::<()>();
+        let (job_done_tx, job_done_rx) = channel::<FrameResult>();
+        let (init_tx, init_rx) = channel::<DocumentInfo>();
 
         let animation_duration = String::from(animation_duration);
         let worker = thread::spawn(move || {
-            let mut renderer =
-                WebVfxRenderer::<S>::new(&url, &html, &animation_duration, width, height);
+            let mut renderer = document.create_renderer(&animation_duration, width, height);
+            if init_tx.send(DocumentInfo::new(&renderer)).is_err() {
+                return;
+            }
+            let mut watcher = FileWatcher::new();
+            source.watch(&mut watcher);
             while let Ok(job) = job_rx.recv() {
+                let mut result = FrameResult::default();
+                watcher.watch_all(renderer.fetched_files());
+                if watcher.changed() {
+                    match source.load() {
+                        Ok(document) => {
+                            log::info!("reloading document {}", document.url);
+                            renderer = document.create_renderer(&animation_duration, width, height);
+                            watcher.clear();
+                            source.watch(&mut watcher);
+                            result.reloaded = Some(DocumentInfo::new(&renderer));
+                        }
+                        Err(e) => {
+                            log::error!("failed to reload document: {e:?}");
+                            result
+                                .errors
+                                .push(format!("failed to reload document: {e:#}"));
+                        }
+                    }
+                }
+
                 let inputs: [&[u8]; S] = job
                     .inputs
                     .into_iter()
@@ -73,30 +329,60 @@ impl<const S: usize> RenderProcessor<S> {
                     .unwrap();
                 let output = unsafe { std::slice::from_raw_parts_mut(job.output.0, job.output.1) };
 
-                renderer.update(job.time, inputs, output);
+                renderer.update(
+                    job.time,
+                    &job.params,
+                    &job.time_params,
+                    job.post.as_ref(),
+                    inputs,
+                    output,
+                );
+                result.errors.extend(renderer.take_errors());
 
-                if job_done_tx.send(()).is_err() {
+                if job_done_tx.send(result).is_err() {
                     return;
                 }
             }
         });
 
+        let document_info = init_rx
+            .recv()
+            .context("WebVfx: worker failed to create renderer")?;
+
         Ok(Self {
             job_tx: Some(job_tx),
             job_done_rx,
             worker: Some(worker),
+            document_info,
         })
     }
 
-    pub fn update(&self, time: f64, inputs: [&[u32]; S], output: &mut [u32]) -> anyhow::Result<()> {
-        let job = RenderJob::new(time, inputs, output);
+    pub fn document_info(&self) -> &DocumentInfo {
+        &self.document_info
+    }
+
+    /// Errors that did not prevent rendering are returned in the `FrameResult`.
+    /// `post` replaces any post-processing declared by the document.
+    pub fn update(
+        &mut self,
+        time: f64,
+        params: &ParamValues,
+        time_params: &TimeParams,
+        post: Option<&PostChain>,
+        inputs: [&[u32]; S],
+        output: &mut [u32],
+    ) -> anyhow::Result<FrameResult> {
+        let job = RenderJob::new(time, params, time_params, post, inputs, output);
         self.job_tx
             .as_ref()
             .unwrap()
             .send(job)
             .map_err(|e| anyhow::anyhow!("Worker thread exited: {e:?}"))?;
-        self.job_done_rx.recv()?;
-        Ok(())
+        let result = self.job_done_rx.recv()?;
+        if let Some(document_info) = &result.reloaded {
+            self.document_info = document_info.clone();
+        }
+        Ok(result)
     }
 }
 
@@ -105,15 +391,24 @@ impl<const S: usize> Drop for RenderProcessor<S> {
         drop(self.job_tx.take().unwrap());
         let worker = self.worker.take().unwrap();
         if let Err(e) = worker.join() {
-            eprintln!("WebVfx: worker failed to exit: {e:?}");
+            log::error!("worker failed to exit: {e:?}");
         }
     }
 }
 
+/// JSON data used to render the HTML as a Tera template
+#[derive(Clone, Debug)]
+pub enum TemplateData {
+    /// Path to a JSON file
+    Path(PathBuf),
+    /// JSON text
+    Json(String),
+}
+
 #[allow(clippy::missing_errors_doc)]
 pub fn process_template(
     html_path: impl AsRef<Path>,
-    json_path: Option<impl AsRef<Path>>,
+    template_data: Option<&TemplateData>,
 ) -> anyhow::Result<(Url, String)> {
     let html_path = html_path.as_ref();
     let absolute_html_path = path::absolute(Path::new(html_path)).with_context(|| {
@@ -134,29 +429,131 @@ pub fn process_template(
             absolute_html_path.display()
         )
     })?;
-    if let Some(json_path) = json_path {
-        let json_path = json_path.as_ref();
-        let json_str = std::fs::read_to_string(json_path).with_context(|| {
-            format!("WebVfx: failed to read JSON path '{}'", json_path.display())
-        })?;
-        let json_value: serde_json::Value = serde_json::from_str(&json_str).with_context(|| {
-            format!(
-                "WebVfx: failed to parse JSON path '{}'",
-                json_path.display()
-            )
-        })?;
-        let context = tera::Context::from_value(json_value).with_context(|| {
-            format!(
-                "WebVfx: failed to configure template context from JSON '{}'",
-                json_path.display()
-            )
-        })?;
-        Ok((
-            url,
-            tera::Tera::one_off(&html, &context, true)
-                .context("WebVfx: Failed to render template")?,
-        ))
-    } else {
-        Ok((url, html))
+    Ok((url, apply_template(html, template_data)?))
+}
+
+/// Process inline HTML markup.
+/// `base_url` may be a URL or a directory path, relative URLs in the HTML are resolved against it.
+/// If not specified, the current directory is used.
+#[allow(clippy::missing_errors_doc)]
+pub fn process_template_source(
+    html_source: &str,
+    base_url: Option<&str>,
+    template_data: Option<&TemplateData>,
+) -> anyhow::Result<(Url, String)> {
+    let url = resolve_base_url(base_url.unwrap_or("."))?;
+    Ok((url, apply_template(html_source.to_string(), template_data)?))
+}
+
+fn resolve_base_url(base_url: &str) -> anyhow::Result<Url> {
+    // Require a scheme longer than 1 char so Windows drive letters are treated as paths
+    if let Ok(url) = Url::parse(base_url)
+        && url.scheme().len() > 1
+    {
+        return Ok(url);
+    }
+    let absolute_path = path::absolute(Path::new(base_url))
+        .with_context(|| format!("WebVfx: failed to make base URL path '{base_url}' absolute"))?;
+    Url::from_directory_path(&absolute_path).map_err(|()| {
+        anyhow::anyhow!(
+            "WebVfx: failed to convert base URL path '{}' to file: URL",
+            absolute_path.display()
+        )
+    })
+}
+
+impl TemplateData {
+    /// Read and parse the JSON
+    fn read(&self) -> anyhow::Result<serde_json::Value> {
+        let json_str = match self {
+            Self::Path(json_path) => std::fs::read_to_string(json_path).with_context(|| {
+                format!("WebVfx: failed to read JSON path '{}'", json_path.display())
+            })?,
+            Self::Json(json_data) => json_data.clone(),
+        };
+        serde_json::from_str(&json_str)
+            .with_context(|| format!("WebVfx: failed to parse JSON {}", self.describe()))
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Path(json_path) => format!("path '{}'", json_path.display()),
+            Self::Json(_) => String::from("data"),
+        }
+    }
+}
+
+fn apply_template(html: String, template_data: Option<&TemplateData>) -> anyhow::Result<String> {
+    let Some(template_data) = template_data else {
+        return Ok(html);
+    };
+    let context = tera::Context::from_value(template_data.read()?).with_context(|| {
+        format!(
+            "WebVfx: failed to configure template context from JSON {}",
+            template_data.describe()
+        )
+    })?;
+    tera::Tera::one_off(&html, &context, true).context("WebVfx: Failed to render template")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::File,
+        time::{Duration, SystemTime},
+    };
+
+    use super::*;
+    use test_support::{HEIGHT, WIDTH};
+
+    #[test]
+    fn test_reload_fetched_file() {
+        let dir = std::env::temp_dir().join(format!("webvfx-processor-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let html_path = dir.join("index.html");
+        let css_path = dir.join("style.css");
+        std::fs::write(
+            &html_path,
+            r#"<html><head><link rel="stylesheet" href="style.css"></head><body></body></html>"#,
+        )
+        .unwrap();
+        std::fs::write(&css_path, "body { background-color: #ff0000; }").unwrap();
+
+        let mut processor = RenderProcessor::<0>::new(
+            &html_path,
+            None,
+            ResourcePaths::default(),
+            "5s",
+            WIDTH,
+            HEIGHT,
+        )
+        .unwrap();
+        let mut output = vec![0u32; (WIDTH * HEIGHT) as usize];
+        let mut render = |time| {
+            processor
+                .update(
+                    time,
+                    &ParamValues::default(),
+                    &TimeParams::default(),
+                    None,
+                    [],
+                    &mut output,
+                )
+                .unwrap();
+            output[0].to_le_bytes()
+        };
+        assert_eq!(render(0.0), [255, 0, 0, 255]);
+
+        // Bump the modification time so the change is seen regardless of filesystem resolution
+        std::fs::write(&css_path, "body { background-color: #0000ff; }").unwrap();
+        File::options()
+            .write(true)
+            .open(&css_path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(render(0.04), [0, 0, 255, 255]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
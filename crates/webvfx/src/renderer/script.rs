@@ -0,0 +1,402 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use blitz_dom::{BaseDocument, DocumentConfig, LocalName, QualName, ns};
+use rhai::{AST, Array, Dynamic, Engine, Map, Scope};
+
+use super::{
+    params::{ParamDeclaration, ParamValue, ParamValues},
+    style::InlineStyles,
+};
+
+pub const WEBVFX_SCRIPT_SELECTOR: &str = r#"script[type="text/webvfx"]"#;
+
+/// Limits keeping a runaway script from hanging the host
+const MAX_OPERATIONS: u64 = 10_000_000;
+const MAX_CALL_LEVELS: usize = 64;
+
+/// State shared with the functions registered on the engine while a script runs
+struct ScriptContext {
+    /// The document being rendered, swapped in for the duration of `Script::run`
+    document: BaseDocument,
+    commands: Vec<Command>,
+    random: u64,
+}
+
+/// Changes requested by a script, applied to the document after the scripts run
+enum Command {
+    SetAttr(usize, LocalName, String),
+    RemoveAttr(usize, LocalName),
+    SetText(usize, String),
+    SetStyle(usize, String),
+}
+
+/// Document changes applied by scripts, and what they replaced so they can be undone
+#[derive(Default)]
+struct AppliedChanges {
+    /// Authored values of attributes by node and name
+    attrs: HashMap<(usize, LocalName), Option<String>>,
+    /// Authored children of elements whose text was set, and the text node replacing them
+    texts: HashMap<usize, (Vec<usize>, usize)>,
+}
+
+impl AppliedChanges {
+    /// Restore the authored attributes and content
+    fn restore(&mut self, document: &mut BaseDocument) {
+        let mut mutator = document.mutate();
+        for ((node_id, name), authored) in self.attrs.drain() {
+            let name = QualName::new(None, ns!(), name);
+            match authored {
+                Some(authored) => mutator.set_attribute(node_id, name, &authored),
+                None => mutator.clear_attribute(node_id, name),
+            }
+        }
+        for (node_id, (children, text_node_id)) in self.texts.drain() {
+            mutator.remove_and_drop_node(text_node_id);
+            mutator.append_children(node_id, &children);
+        }
+    }
+
+    fn apply(&mut self, document: &mut BaseDocument, command: Command, styles: &mut InlineStyles) {
+        match command {
+            Command::SetAttr(node_id, name, value) => {
+                self.save_attr(document, node_id, &name);
+                document
+                    .mutate()
+                    .set_attribute(node_id, QualName::new(None, ns!(), name), &value);
+            }
+            Command::RemoveAttr(node_id, name) => {
+                self.save_attr(document, node_id, &name);
+                document
+                    .mutate()
+                    .clear_attribute(node_id, QualName::new(None, ns!(), name));
+            }
+            Command::SetText(node_id, text) => {
+                if let Some((_, text_node_id)) = self.texts.get(&node_id) {
+                    document.mutate().set_node_text(*text_node_id, &text);
+                    return;
+                }
+                let children = document
+                    .get_node(node_id)
+                    .map(|node| node.children.clone())
+                    .unwrap_or_default();
+                let mut mutator = document.mutate();
+                for child_id in &children {
+                    mutator.remove_node(*child_id);
+                }
+                let text_node_id = mutator.create_text_node(&text);
+                mutator.append_children(node_id, &[text_node_id]);
+                self.texts.insert(node_id, (children, text_node_id));
+            }
+            Command::SetStyle(node_id, declarations) => styles.push(node_id, &declarations),
+        }
+    }
+
+    fn save_attr(&mut self, document: &BaseDocument, node_id: usize, name: &LocalName) {
+        self.attrs
+            .entry((node_id, name.clone()))
+            .or_insert_with(|| {
+                document
+                    .get_node(node_id)
+                    .and_then(|node| node.attr(name.clone()))
+                    .map(String::from)
+            });
+    }
+}
+
+/// [Rhai](https://rhai.rs) scripts from `<script type="text/webvfx">` elements,
+/// run before each frame is rendered.
+///
+/// Scripts get the constants `time` (seconds), `params` (a map of declared params
+/// by name and the generic `param1`.., `color1`.., `position1`.. and `bool1`.. slots)
+/// and `data` (the template JSON), and these functions:
+/// `query(selector)` returns an array of node ids,
+/// `attr(node, name)`, `set_attr(node, name, value)`, `remove_attr(node, name)`,
+/// `text(node)`, `set_text(node, text)`, `set_style(node, declarations)`
+/// and `random()` which returns a number in the range 0..1.
+///
+/// Changes are applied to the document after the scripts run, and undone before they run
+/// the next frame, so scripts always see the document as authored.
+/// Scripts run from scratch each frame and have no access to the clock, and `random()` is
+/// seeded from the time, so a frame renders the same regardless of the frames before it.
+pub struct Script {
+    engine: Engine,
+    asts: Vec<AST>,
+    context: Rc<RefCell<ScriptContext>>,
+    applied: AppliedChanges,
+    data: Dynamic,
+    /// Last error reported, to avoid repeating it every frame
+    last_error: Option<String>,
+}
+
+impl Script {
+    /// Compile all scripts in the document, returns `None` if there are none
+    pub fn from_document(document: &BaseDocument, errors: &mut Vec<String>) -> Option<Self> {
+        let sources: Vec<String> = document
+            .query_selector_all(WEBVFX_SCRIPT_SELECTOR)
+            .ok()?
+            .iter()
+            .filter_map(|node_id| document.get_node(*node_id))
+            .map(blitz_dom::Node::text_content)
+            .collect();
+        if sources.is_empty() {
+            return None;
+        }
+
+        let context = Rc::new(RefCell::new(ScriptContext {
+            document: BaseDocument::new(DocumentConfig::default()),
+            commands: Vec::new(),
+            random: 0,
+        }));
+        let engine = create_engine(&context);
+        let asts = sources
+            .iter()
+            .filter_map(|source| match engine.compile(source) {
+                Ok(ast) => Some(ast),
+                Err(e) => {
+                    log::warn!("failed to compile script: {e}");
+                    errors.push(format!("failed to compile script: {e}"));
+                    None
+                }
+            })
+            .collect();
+        Some(Self {
+            engine,
+            asts,
+            context,
+            applied: AppliedChanges::default(),
+            data: Dynamic::UNIT,
+            last_error: None,
+        })
+    }
+
+    /// Template data exposed to scripts as `data`
+    pub fn set_data(&mut self, data: &serde_json::Value) {
+        self.data = rhai::serde::to_dynamic(data).unwrap_or(Dynamic::UNIT);
+    }
+
+    /// Forget the changes applied to a previous document, before running on a reloaded one
+    pub fn reset(&mut self) {
+        self.applied = AppliedChanges::default();
+    }
+
+    /// Run the scripts, styles they set are pushed to `inline_styles`.
+    /// Returns an error if a script fails, unless it is the same error as the previous frame.
+    pub fn run(
+        &mut self,
+        time: f64,
+        params: &ParamValues,
+        declarations: &[ParamDeclaration],
+        document: &mut BaseDocument,
+        inline_styles: &mut InlineStyles,
+    ) -> Option<String> {
+        self.applied.restore(document);
+        {
+            let mut context = self.context.borrow_mut();
+            std::mem::swap(&mut context.document, document);
+            context.commands.clear();
+            context.random = time.to_bits();
+        }
+
+        let mut scope = Scope::new();
+        scope.push_constant("time", time);
+        scope.push_constant("params", params_map(params, declarations));
+        scope.push_constant("data", self.data.clone());
+        let mut error = None;
+        for ast in &self.asts {
+            if let Err(e) = self.engine.run_ast_with_scope(&mut scope, ast) {
+                error = Some(format!("script failed: {e}"));
+                break;
+            }
+        }
+
+        let mut context = self.context.borrow_mut();
+        std::mem::swap(&mut context.document, document);
+        for command in context.commands.drain(..) {
+            self.applied.apply(document, command, inline_styles);
+        }
+
+        if error == self.last_error {
+            return None;
+        }
+        self.last_error.clone_from(&error);
+        if let Some(error) = &error {
+            log::warn!("{error}");
+        }
+        error
+    }
+}
+
+fn create_engine(context: &Rc<RefCell<ScriptContext>>) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .on_print(|message| log::info!("script: {message}"))
+        .on_debug(|message, _, position| log::debug!("script {position}: {message}"));
+
+    let ctx = context.clone();
+    engine.register_fn("query", move |selector: &str| -> Array {
+        ctx.borrow()
+            .document
+            .query_selector_all(selector)
+            .map(|node_ids| {
+                node_ids
+                    .iter()
+                    .filter_map(|node_id| i64::try_from(*node_id).ok())
+                    .map(Dynamic::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("attr", move |node_id: i64, name: &str| -> Dynamic {
+        usize::try_from(node_id)
+            .ok()
+            .and_then(|node_id| {
+                ctx.borrow()
+                    .document
+                    .get_node(node_id)?
+                    .attr(LocalName::from(name))
+                    .map(String::from)
+            })
+            .map_or(Dynamic::UNIT, Dynamic::from)
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("set_attr", move |node_id: i64, name: &str, value: &str| {
+        push_command(&ctx, node_id, |node_id| {
+            Command::SetAttr(node_id, LocalName::from(name), value.to_string())
+        });
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("remove_attr", move |node_id: i64, name: &str| {
+        push_command(&ctx, node_id, |node_id| {
+            Command::RemoveAttr(node_id, LocalName::from(name))
+        });
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("text", move |node_id: i64| -> String {
+        let context = ctx.borrow();
+        valid_node(&context.document, node_id)
+            .and_then(|node_id| context.document.get_node(node_id))
+            .map(blitz_dom::Node::text_content)
+            .unwrap_or_default()
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("set_text", move |node_id: i64, text: &str| {
+        push_command(&ctx, node_id, |node_id| {
+            Command::SetText(node_id, text.to_string())
+        });
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("set_style", move |node_id: i64, declarations: &str| {
+        push_command(&ctx, node_id, |node_id| {
+            Command::SetStyle(node_id, declarations.to_string())
+        });
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("random", move || -> f64 {
+        next_random(&mut ctx.borrow_mut().random)
+    });
+
+    engine
+}
+
+/// Queue a change to an element, ignoring ids that are not elements
+fn push_command(
+    context: &RefCell<ScriptContext>,
+    node_id: i64,
+    command: impl FnOnce(usize) -> Command,
+) {
+    let mut context = context.borrow_mut();
+    if let Some(node_id) = valid_node(&context.document, node_id) {
+        context.commands.push(command(node_id));
+    }
+}
+
+fn valid_node(document: &BaseDocument, node_id: i64) -> Option<usize> {
+    usize::try_from(node_id).ok().filter(|node_id| {
+        document
+            .get_node(*node_id)
+            .is_some_and(|node| node.is_element())
+    })
+}
+
+/// Advance the generator state and return a number in the range 0..1
+#[allow(clippy::cast_precision_loss)]
+fn next_random(state: &mut u64) -> f64 {
+    *state = splitmix64(*state);
+    (*state >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn params_map(params: &ParamValues, declarations: &[ParamDeclaration]) -> Map {
+    let mut map = Map::new();
+    let resolved = params.resolve_slots(declarations);
+    let slots = resolved
+        .doubles
+        .map(|v| ("param", ParamValue::Double(v.unwrap_or_default())))
+        .into_iter()
+        .enumerate()
+        .chain(
+            resolved
+                .colors
+                .map(|v| ("color", ParamValue::Color(v.unwrap_or_default())))
+                .into_iter()
+                .enumerate(),
+        )
+        .chain(
+            resolved
+                .positions
+                .map(|v| ("position", ParamValue::Position(v.unwrap_or_default())))
+                .into_iter()
+                .enumerate(),
+        )
+        .chain(
+            resolved
+                .bools
+                .map(|v| ("bool", ParamValue::Bool(v.unwrap_or_default())))
+                .into_iter()
+                .enumerate(),
+        );
+    for (i, (prefix, value)) in slots {
+        map.insert(format!("{prefix}{}", i + 1).into(), param_dynamic(value));
+    }
+    for declaration in declarations {
+        map.insert(
+            declaration.name.as_str().into(),
+            param_dynamic(declaration.resolve(params)),
+        );
+    }
+    map
+}
+
+fn param_dynamic(value: ParamValue) -> Dynamic {
+    match value {
+        ParamValue::Double(v) => Dynamic::from(v),
+        ParamValue::Color(rgb) => Dynamic::from_array(
+            rgb.into_iter()
+                .map(|c| Dynamic::from(f64::from(c)))
+                .collect(),
+        ),
+        ParamValue::Position(xy) => {
+            Dynamic::from_array(xy.into_iter().map(Dynamic::from).collect())
+        }
+        ParamValue::Bool(v) => Dynamic::from(v),
+    }
+}
@@ -0,0 +1,212 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use blitz_dom::{BaseDocument, LocalName, Node};
+
+use super::{
+    behaviors::{WEBVFX_DELAY_ATTRIBUTE, WEBVFX_DURATION_ATTRIBUTE, parse_duration},
+    style::InlineStyles,
+};
+
+pub const WEBVFX_SCROLL_SELECTOR: &str = "[data-webvfx-scroll]";
+pub const WEBVFX_SCROLL_ATTRIBUTE: &str = "data-webvfx-scroll";
+/// Scroll rate in pixels per second, instead of a duration
+pub const WEBVFX_SPEED_ATTRIBUTE: &str = "data-webvfx-speed";
+pub const WEBVFX_LOOP_ATTRIBUTE: &str = "data-webvfx-loop";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Debug, PartialEq)]
+struct Scroll {
+    direction: Direction,
+    delay: f64,
+    duration: Option<f64>,
+    speed: Option<f64>,
+    looping: bool,
+}
+
+/// Elements scrolled through their parent by `data-webvfx-scroll="up|down|left|right"`,
+/// e.g. credit rolls and tickers. The element enters from one edge of its parent and scrolls
+/// until it has left through the opposite edge, over `data-webvfx-duration` (default the clip
+/// duration) or at `data-webvfx-speed` pixels per second, after `data-webvfx-delay`.
+/// With `data-webvfx-loop` it scrolls through again, continuously.
+///
+/// The offset is set as an inline `transform`, replacing any authored transform,
+/// so the parent usually has `overflow: hidden`. Sizes are taken from the layout of
+/// the previous frame, since transforms do not affect layout.
+#[derive(Default)]
+pub struct ScrollBehaviors {
+    elements: Vec<(usize, Scroll)>,
+}
+
+impl ScrollBehaviors {
+    pub fn new(document: &BaseDocument) -> Self {
+        let mut scroll = Self::default();
+        scroll.bind(document);
+        scroll
+    }
+
+    /// Find the scrolling elements in a new document
+    pub fn bind(&mut self, document: &BaseDocument) {
+        self.elements = document
+            .query_selector_all(WEBVFX_SCROLL_SELECTOR)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|node_id| {
+                let node = document.get_node(*node_id)?;
+                match Scroll::from_node(node) {
+                    Ok(scroll) => Some((*node_id, scroll)),
+                    Err(e) => {
+                        log::warn!("invalid scroll: {e}");
+                        None
+                    }
+                }
+            })
+            .collect();
+    }
+
+    /// Push the transforms scrolling the elements at `time` to `inline_styles`
+    pub fn apply(
+        &self,
+        time: f64,
+        clip_duration: Option<f64>,
+        document: &BaseDocument,
+        inline_styles: &mut InlineStyles,
+    ) {
+        for (node_id, scroll) in &self.elements {
+            let Some(node) = document.get_node(*node_id) else {
+                continue;
+            };
+            let Some(parent) = node
+                .parent
+                .and_then(|parent_id| document.get_node(parent_id))
+            else {
+                continue;
+            };
+            let layout = &node.final_layout;
+            let parent_size = parent.final_layout.size;
+            let (position, size, parent_size) = match scroll.direction {
+                Direction::Up | Direction::Down => {
+                    (layout.location.y, layout.size.height, parent_size.height)
+                }
+                Direction::Left | Direction::Right => {
+                    (layout.location.x, layout.size.width, parent_size.width)
+                }
+            };
+            let offset = scroll.offset(
+                time,
+                clip_duration,
+                f64::from(position),
+                f64::from(size),
+                f64::from(parent_size),
+            );
+            let translate = match scroll.direction {
+                Direction::Up | Direction::Down => "translateY",
+                Direction::Left | Direction::Right => "translateX",
+            };
+            inline_styles.push(*node_id, &format!("transform: {translate}({offset}px);"));
+        }
+    }
+}
+
+impl Scroll {
+    fn from_node(node: &Node) -> anyhow::Result<Self> {
+        let attr = |name: &str| node.attr(LocalName::from(name)).map(str::trim);
+        let direction = match attr(WEBVFX_SCROLL_ATTRIBUTE).unwrap_or_default() {
+            "" | "up" => Direction::Up,
+            "down" => Direction::Down,
+            "left" => Direction::Left,
+            "right" => Direction::Right,
+            direction => return Err(anyhow::anyhow!("direction '{direction}'")),
+        };
+        let seconds = |name: &str| {
+            attr(name)
+                .filter(|value| !value.is_empty())
+                .map(|value| {
+                    parse_duration(value).ok_or_else(|| anyhow::anyhow!("{name} '{value}'"))
+                })
+                .transpose()
+        };
+        let speed = attr(WEBVFX_SPEED_ATTRIBUTE)
+            .map(|speed| {
+                speed
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|speed| *speed > 0.0)
+                    .ok_or_else(|| anyhow::anyhow!("speed '{speed}'"))
+            })
+            .transpose()?;
+        Ok(Self {
+            direction,
+            delay: seconds(WEBVFX_DELAY_ATTRIBUTE)?.unwrap_or(0.0),
+            duration: seconds(WEBVFX_DURATION_ATTRIBUTE)?,
+            speed,
+            looping: attr(WEBVFX_LOOP_ATTRIBUTE).is_some_and(|looping| looping != "false"),
+        })
+    }
+
+    /// Translation of the element at `time`, given its position and size
+    /// along the scroll direction and the size of its parent
+    fn offset(
+        &self,
+        time: f64,
+        clip_duration: Option<f64>,
+        position: f64,
+        size: f64,
+        parent_size: f64,
+    ) -> f64 {
+        let distance = parent_size + size;
+        let time = (time - self.delay).max(0.0);
+        let travelled = match (self.speed, self.duration.or(clip_duration)) {
+            (Some(speed), _) => speed * time,
+            (None, Some(duration)) if duration > 0.0 => distance * time / duration,
+            _ => 0.0,
+        };
+        let travelled = if self.looping && distance > 0.0 {
+            travelled.rem_euclid(distance)
+        } else {
+            travelled.min(distance)
+        };
+        match self.direction {
+            Direction::Up | Direction::Left => parent_size - position - travelled,
+            Direction::Down | Direction::Right => travelled - position - size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset() {
+        let credits = Scroll {
+            direction: Direction::Up,
+            delay: 1.0,
+            duration: None,
+            speed: None,
+            looping: false,
+        };
+        // 200px tall credits in a 100px tall parent, over a 4s clip
+        assert!((credits.offset(0.0, Some(4.0), 0.0, 200.0, 100.0) - 100.0).abs() < 1e-9);
+        assert!((credits.offset(3.0, Some(4.0), 0.0, 200.0, 100.0) + 50.0).abs() < 1e-9);
+        assert!((credits.offset(9.0, Some(4.0), 0.0, 200.0, 100.0) + 200.0).abs() < 1e-9);
+
+        let ticker = Scroll {
+            direction: Direction::Left,
+            delay: 0.0,
+            duration: None,
+            speed: Some(100.0),
+            looping: true,
+        };
+        // 300px wide ticker at x 20px in a 500px wide parent, loops every 8s
+        assert!((ticker.offset(1.0, None, 20.0, 300.0, 500.0) - 380.0).abs() < 1e-9);
+        assert!((ticker.offset(9.0, None, 20.0, 300.0, 500.0) - 380.0).abs() < 1e-9);
+    }
+}
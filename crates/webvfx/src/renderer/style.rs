@@ -0,0 +1,83 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+
+use blitz_dom::{BaseDocument, local_name, qual_name};
+
+/// Layers generated declarations on top of the authored `style` attribute of elements.
+/// Declarations are collected with `push` each frame and written to the document by `apply`,
+/// elements that received no declarations are restored to their authored style.
+#[derive(Default)]
+pub struct InlineStyles {
+    authored: HashMap<usize, Option<String>>,
+    pending: HashMap<usize, String>,
+    applied: HashMap<usize, String>,
+}
+
+impl InlineStyles {
+    pub fn push(&mut self, node_id: usize, declarations: &str) {
+        let pending = self.pending.entry(node_id).or_default();
+        pending.push_str(declarations);
+        if !declarations.trim_end().ends_with(';') {
+            pending.push(';');
+        }
+    }
+
+    pub fn apply(&mut self, document: &mut BaseDocument) {
+        let pending = std::mem::take(&mut self.pending);
+        let mut mutator = document.mutate();
+        for (node_id, _) in self
+            .applied
+            .iter()
+            .filter(|(node_id, _)| !pending.contains_key(node_id))
+        {
+            match self.authored.get(node_id).cloned().flatten() {
+                Some(authored) => {
+                    mutator.set_attribute(*node_id, qual_name!("style"), &authored);
+                }
+                None => mutator.clear_attribute(*node_id, qual_name!("style")),
+            }
+        }
+        drop(mutator);
+
+        for (node_id, declarations) in &pending {
+            if self.applied.get(node_id) != Some(declarations) {
+                self.write_style(document, *node_id, declarations);
+            }
+        }
+        self.applied = pending;
+    }
+
+    /// Declarations written to the element by the last `apply` or `write`
+    pub fn applied(&self, node_id: usize) -> Option<&str> {
+        self.applied.get(&node_id).map(String::as_str)
+    }
+
+    /// Replace the declarations applied to the element and write them immediately,
+    /// for declarations that depend on the layout resulting from `apply`
+    pub fn write(&mut self, document: &mut BaseDocument, node_id: usize, declarations: String) {
+        self.write_style(document, node_id, &declarations);
+        self.applied.insert(node_id, declarations);
+    }
+
+    fn write_style(&mut self, document: &mut BaseDocument, node_id: usize, declarations: &str) {
+        let authored = self
+            .authored
+            .entry(node_id)
+            .or_insert_with(|| {
+                document
+                    .get_node(node_id)
+                    .and_then(|node| node.attr(local_name!("style")))
+                    .map(String::from)
+            })
+            .as_deref();
+        let style = match authored {
+            Some(authored) => format!("{authored}; {declarations}"),
+            None => declarations.to_string(),
+        };
+        document
+            .mutate()
+            .set_attribute(node_id, qual_name!("style"), &style);
+    }
+}
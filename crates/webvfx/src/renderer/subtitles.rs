@@ -0,0 +1,430 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+
+use blitz_dom::{
+    BaseDocument, DocumentMutator, LocalName, QualName, local_name, node::Attribute, ns, qual_name,
+};
+
+pub const WEBVFX_SUBTITLE_SELECTOR: &str = ".webvfx-subtitle";
+pub const WEBVFX_SUBTITLES_ATTRIBUTE: &str = "data-webvfx-subtitles";
+
+/// Classes of the spans the `<b>`, `<i>` and `<u>` cue tags map to,
+/// styled by the user agent stylesheet so documents can override them
+pub const WEBVFX_CUE_BOLD_CLASS: &str = "webvfx-cue-b";
+pub const WEBVFX_CUE_ITALIC_CLASS: &str = "webvfx-cue-i";
+pub const WEBVFX_CUE_UNDERLINE_CLASS: &str = "webvfx-cue-u";
+pub const WEBVFX_CUE_VOICE_CLASS: &str = "webvfx-cue-v";
+
+/// Cues parsed from an SRT or WebVTT file
+#[derive(Debug, Default)]
+pub struct Subtitles {
+    cues: Vec<Cue>,
+}
+
+#[derive(Debug)]
+struct Cue {
+    start: f64,
+    end: f64,
+    content: Vec<CueNode>,
+}
+
+#[derive(Debug, PartialEq)]
+enum CueNode {
+    Text(String),
+    LineBreak,
+    Span(Span),
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Span {
+    attributes: Vec<(&'static str, String)>,
+    children: Vec<CueNode>,
+}
+
+impl Subtitles {
+    /// Parse SRT or WebVTT, WebVTT is detected by its `WEBVTT` header
+    #[allow(clippy::missing_errors_doc)]
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let text = text.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+        let mut blocks = text.split("\n\n").map(str::trim).peekable();
+        let vtt = blocks
+            .peek()
+            .is_some_and(|header| header.starts_with("WEBVTT"));
+        if vtt {
+            blocks.next();
+        }
+        let mut cues = Vec::new();
+        for block in blocks.filter(|block| !block.is_empty()) {
+            if vtt
+                && (block.starts_with("NOTE")
+                    || block.starts_with("STYLE")
+                    || block.starts_with("REGION"))
+            {
+                continue;
+            }
+            // The timing line may be preceded by a cue identifier
+            let mut lines = block.lines().skip_while(|line| !line.contains("-->"));
+            let Some(timing) = lines.next() else {
+                return Err(anyhow::anyhow!("missing cue timing in '{block}'"));
+            };
+            let (start, end) = timing.split_once("-->").expect("timing line contains -->");
+            // WebVTT cue settings follow the end time
+            let end = end.split_whitespace().next().unwrap_or_default();
+            let (Some(start), Some(end)) = (parse_timestamp(start), parse_timestamp(end)) else {
+                return Err(anyhow::anyhow!("invalid cue timing '{timing}'"));
+            };
+            cues.push(Cue {
+                start,
+                end,
+                content: parse_cue_text(&lines.collect::<Vec<_>>().join("\n")),
+            });
+        }
+        cues.sort_by(|a, b| a.start.total_cmp(&b.start));
+        Ok(Self { cues })
+    }
+
+    /// Indices of the cues active at `time`
+    fn active_cues(&self, time: f64) -> Vec<usize> {
+        self.cues
+            .iter()
+            .enumerate()
+            .take_while(|(_, cue)| cue.start <= time)
+            .filter(|(_, cue)| time < cue.end)
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+/// Parse `hh:mm:ss.ttt`, `mm:ss.ttt` or the SRT `hh:mm:ss,ttt` into seconds
+pub(super) fn parse_timestamp(timestamp: &str) -> Option<f64> {
+    let timestamp = timestamp.trim().replace(',', ".");
+    let mut parts = timestamp.rsplit(':');
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    let minutes: f64 = parts.next()?.parse().ok()?;
+    let hours: f64 = parts.next().map_or(Some(0.0), |hours| hours.parse().ok())?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// Parse cue text markup, `<b>`, `<i>`, `<u>`, `<c.class>`, `<v Speaker>`, `<lang tag>`
+/// and the SRT `<font color="...">` become spans. Other tags are dropped, keeping their content.
+fn parse_cue_text(text: &str) -> Vec<CueNode> {
+    let mut root = Vec::new();
+    let mut open: Vec<(String, Span)> = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (content, tag) = match rest.find('<') {
+            Some(tag_start) => match rest[tag_start..].find('>') {
+                Some(tag_len) => {
+                    let tag = &rest[tag_start + 1..tag_start + tag_len];
+                    let content = &rest[..tag_start];
+                    rest = &rest[tag_start + tag_len + 1..];
+                    (content, Some(tag))
+                }
+                None => (std::mem::take(&mut rest), None),
+            },
+            None => (std::mem::take(&mut rest), None),
+        };
+
+        let children = open
+            .last_mut()
+            .map_or(&mut root, |(_, span)| &mut span.children);
+        for (i, line) in content.split('\n').enumerate() {
+            if i > 0 {
+                children.push(CueNode::LineBreak);
+            }
+            if !line.is_empty() {
+                children.push(CueNode::Text(decode_entities(line)));
+            }
+        }
+
+        match tag.map(str::trim) {
+            Some(tag) if tag.starts_with('/') => {
+                let name = tag[1..].trim().to_ascii_lowercase();
+                if let Some(index) = open.iter().rposition(|(open_name, _)| *open_name == name) {
+                    while open.len() > index {
+                        close_span(&mut open, &mut root);
+                    }
+                }
+            }
+            Some(tag) => {
+                if let Some((name, span)) = open_span(tag) {
+                    open.push((name, span));
+                }
+            }
+            None => {}
+        }
+    }
+    while !open.is_empty() {
+        close_span(&mut open, &mut root);
+    }
+    root
+}
+
+fn close_span(open: &mut Vec<(String, Span)>, root: &mut Vec<CueNode>) {
+    if let Some((_, span)) = open.pop() {
+        open.last_mut()
+            .map_or(root, |(_, parent)| &mut parent.children)
+            .push(CueNode::Span(span));
+    }
+}
+
+/// Create the span for an opening tag, e.g. `c.yellow.large` or `v Bob`
+fn open_span(tag: &str) -> Option<(String, Span)> {
+    let (name, annotation) = tag
+        .split_once(char::is_whitespace)
+        .map_or((tag, ""), |(name, annotation)| (name, annotation.trim()));
+    let mut classes = name.split('.');
+    let name = classes.next()?.to_ascii_lowercase();
+    let mut classes: Vec<&str> = classes.filter(|class| !class.is_empty()).collect();
+    let mut span = Span::default();
+    match name.as_str() {
+        "b" => classes.push(WEBVFX_CUE_BOLD_CLASS),
+        "i" => classes.push(WEBVFX_CUE_ITALIC_CLASS),
+        "u" => classes.push(WEBVFX_CUE_UNDERLINE_CLASS),
+        "c" => {}
+        "v" => {
+            classes.push(WEBVFX_CUE_VOICE_CLASS);
+            span.attributes
+                .push(("data-voice", decode_entities(annotation)));
+        }
+        "lang" => span.attributes.push(("lang", annotation.to_string())),
+        "font" => {
+            if let Some(color) = font_color(annotation) {
+                span.attributes.push(("style", format!("color: {color}")));
+            }
+        }
+        // Timestamp tags and unsupported tags such as ruby
+        _ => return None,
+    }
+    if !classes.is_empty() {
+        span.attributes.push(("class", classes.join(" ")));
+    }
+    Some((name, span))
+}
+
+/// The color of a `<font color="...">` tag
+fn font_color(attributes: &str) -> Option<&str> {
+    let (_, value) = attributes.split_once("color=")?;
+    let value = value.trim_start();
+    let color = match value.strip_prefix(['"', '\'']) {
+        Some(quoted) => quoted.split(['"', '\'']).next()?,
+        None => value.split_whitespace().next()?,
+    };
+    color
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '#')
+        .then_some(color)
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", "\u{a0}")
+        .replace("&lrm;", "\u{200e}")
+        .replace("&rlm;", "\u{200f}")
+        .replace("&amp;", "&")
+}
+
+/// Shows the active cues in `.webvfx-subtitle` elements.
+/// Elements show the subtitles from their `data-webvfx-subtitles` attribute,
+/// unless subtitles are set for all elements with `set_subtitles`.
+#[derive(Default)]
+pub struct SubtitlePlayer {
+    subtitles: Option<Subtitles>,
+    /// Subtitles by `data-webvfx-subtitles` attribute value
+    linked: HashMap<String, Subtitles>,
+    /// Element node ids and the attribute value of their subtitles, `None` for `subtitles`
+    nodes: Vec<(usize, Option<String>)>,
+    /// Cues currently shown by each element
+    shown: HashMap<usize, Vec<usize>>,
+}
+
+impl SubtitlePlayer {
+    /// Attribute values of the subtitle elements in the document
+    pub fn linked_sources(document: &BaseDocument) -> Vec<String> {
+        let mut sources: Vec<String> = document
+            .query_selector_all(WEBVFX_SUBTITLE_SELECTOR)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|node_id| document.get_node(*node_id))
+            .filter_map(|node| node.attr(LocalName::from(WEBVFX_SUBTITLES_ATTRIBUTE)))
+            .map(String::from)
+            .collect();
+        sources.sort();
+        sources.dedup();
+        sources
+    }
+
+    pub fn insert_linked(&mut self, source: String, subtitles: Subtitles) {
+        self.linked.insert(source, subtitles);
+    }
+
+    /// Subtitles for all elements, overriding their `data-webvfx-subtitles` attribute
+    pub fn set_subtitles(&mut self, subtitles: Subtitles) {
+        self.subtitles = Some(subtitles);
+    }
+
+    /// Find the subtitle elements in a new document
+    pub fn bind(&mut self, document: &BaseDocument) {
+        self.shown.clear();
+        self.nodes = document
+            .query_selector_all(WEBVFX_SUBTITLE_SELECTOR)
+            .unwrap_or_default()
+            .iter()
+            .copied()
+            .filter_map(|node_id| {
+                if self.subtitles.is_some() {
+                    return Some((node_id, None));
+                }
+                document
+                    .get_node(node_id)?
+                    .attr(LocalName::from(WEBVFX_SUBTITLES_ATTRIBUTE))
+                    .filter(|source| self.linked.contains_key(*source))
+                    .map(|source| (node_id, Some(source.to_string())))
+            })
+            .collect();
+    }
+
+    /// Replace the content of subtitle elements whose active cues changed
+    pub fn apply(&mut self, time: f64, document: &mut BaseDocument) {
+        for (node_id, source) in &self.nodes {
+            let subtitles = match source {
+                Some(source) => self.linked.get(source),
+                None => self.subtitles.as_ref(),
+            };
+            let Some(subtitles) = subtitles else {
+                continue;
+            };
+            let active = subtitles.active_cues(time);
+            if self.shown.get(node_id) == Some(&active) {
+                continue;
+            }
+            let children = document
+                .get_node(*node_id)
+                .map(|node| node.children.clone())
+                .unwrap_or_default();
+            let mut mutator = document.mutate();
+            for child_id in children {
+                mutator.remove_and_drop_node(child_id);
+            }
+            for (i, index) in active.iter().enumerate() {
+                if i > 0 {
+                    let br_id = mutator.create_element(qual_name!("br", html), Vec::new());
+                    mutator.append_children(*node_id, &[br_id]);
+                }
+                let child_ids = create_nodes(&mut mutator, &subtitles.cues[*index].content);
+                mutator.append_children(*node_id, &child_ids);
+            }
+            drop(mutator);
+            self.shown.insert(*node_id, active);
+        }
+    }
+}
+
+fn create_nodes(mutator: &mut DocumentMutator, nodes: &[CueNode]) -> Vec<usize> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            CueNode::Text(text) => mutator.create_text_node(text),
+            CueNode::LineBreak => mutator.create_element(qual_name!("br", html), Vec::new()),
+            CueNode::Span(span) => {
+                let attributes = span
+                    .attributes
+                    .iter()
+                    .map(|(name, value)| Attribute {
+                        name: QualName::new(None, ns!(), LocalName::from(*name)),
+                        value: value.clone(),
+                    })
+                    .collect();
+                let span_id = mutator.create_element(
+                    QualName::new(None, ns!(html), local_name!("span")),
+                    attributes,
+                );
+                let child_ids = create_nodes(mutator, &span.children);
+                mutator.append_children(span_id, &child_ids);
+                span_id
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("00:01:02,500"), Some(62.5));
+        assert_eq!(parse_timestamp("01:00:00.000"), Some(3600.0));
+        assert_eq!(parse_timestamp("01:02.250"), Some(62.25));
+        assert_eq!(parse_timestamp("1.5"), None);
+    }
+
+    #[test]
+    fn test_parse_srt() {
+        let subtitles = Subtitles::parse(
+            "1\r\n00:00:01,000 --> 00:00:02,000\r\nHello\r\n<i>world</i>\r\n\r\n\
+             2\r\n00:00:01,500 --> 00:00:03,000\r\n<font color=\"#ff0000\">Red</font>\r\n",
+        )
+        .unwrap();
+        assert_eq!(subtitles.active_cues(0.5), Vec::<usize>::new());
+        assert_eq!(subtitles.active_cues(1.75), vec![0, 1]);
+        assert_eq!(subtitles.active_cues(2.0), vec![1]);
+        assert_eq!(
+            subtitles.cues[0].content,
+            vec![
+                CueNode::Text("Hello".into()),
+                CueNode::LineBreak,
+                CueNode::Span(Span {
+                    attributes: vec![("class", WEBVFX_CUE_ITALIC_CLASS.into())],
+                    children: vec![CueNode::Text("world".into())],
+                }),
+            ]
+        );
+        assert_eq!(
+            subtitles.cues[1].content,
+            vec![CueNode::Span(Span {
+                attributes: vec![("style", "color: #ff0000".into())],
+                children: vec![CueNode::Text("Red".into())],
+            })]
+        );
+    }
+
+    #[test]
+    fn test_parse_vtt() {
+        let subtitles = Subtitles::parse(
+            "WEBVTT\n\nNOTE a comment\n\nintro\n00:01.000 --> 00:02.000 align:start\n\
+             <v Bob>Hi <b.loud>there</v> &amp; bye\n",
+        )
+        .unwrap();
+        assert_eq!(subtitles.active_cues(1.0), vec![0]);
+        assert_eq!(
+            subtitles.cues[0].content,
+            vec![
+                CueNode::Span(Span {
+                    attributes: vec![
+                        ("data-voice", "Bob".into()),
+                        ("class", WEBVFX_CUE_VOICE_CLASS.into())
+                    ],
+                    children: vec![
+                        CueNode::Text("Hi ".into()),
+                        CueNode::Span(Span {
+                            attributes: vec![("class", format!("loud {WEBVFX_CUE_BOLD_CLASS}"))],
+                            children: vec![CueNode::Text("there".into())],
+                        }),
+                    ],
+                }),
+                CueNode::Text(" & bye".into()),
+            ]
+        );
+        assert!(Subtitles::parse("WEBVTT\n\n00:01.000 --> bogus\nHi").is_err());
+    }
+}
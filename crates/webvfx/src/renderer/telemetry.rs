@@ -0,0 +1,637 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{collections::HashMap, fmt::Write};
+
+use blitz_dom::{BaseDocument, LocalName, QualName, local_name, node::Attribute, ns};
+
+use super::{style::InlineStyles, timeline::set_text};
+
+pub const WEBVFX_TELEMETRY_LINK_SELECTOR: &str = r#"link[rel="webvfx-telemetry"]"#;
+pub const WEBVFX_CSS_TELEMETRY_PREFIX: &str = "--webvfx-telemetry-";
+pub const WEBVFX_FIELD_SELECTOR: &str = "[data-webvfx-field]";
+pub const WEBVFX_FIELD_ATTRIBUTE: &str = "data-webvfx-field";
+/// Decimal places of a field value, default 0
+pub const WEBVFX_PRECISION_ATTRIBUTE: &str = "data-webvfx-precision";
+/// Multiplier applied to a field value, e.g. 3.6 to show a speed in m/s as km/h
+pub const WEBVFX_SCALE_ATTRIBUTE: &str = "data-webvfx-scale";
+pub const WEBVFX_ROUTE_SELECTOR: &str = ".webvfx-route";
+pub const WEBVFX_ROUTE_PATH_CLASS: &str = "webvfx-route-path";
+pub const WEBVFX_ROUTE_MARKER_CLASS: &str = "webvfx-route-marker";
+pub const WEBVFX_ROUTE_STROKE_ATTRIBUTE: &str = "data-webvfx-route-stroke";
+pub const WEBVFX_ROUTE_STROKE_WIDTH_ATTRIBUTE: &str = "data-webvfx-route-stroke-width";
+
+/// Longest side of the route in SVG user units
+const ROUTE_SIZE: f64 = 1000.0;
+const ROUTE_PADDING: f64 = 20.0;
+const EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// Time indexed numeric values, e.g. from an action camera or GPS device.
+///
+/// CSV files have a header row naming the fields and a `time` (or `timestamp`) column,
+/// times are seconds or RFC 3339 date-times. GPX track points provide `lat`, `lon`,
+/// `altitude` and numeric extensions, `hr`, `cad` and `atemp` are named `heart-rate`,
+/// `cadence` and `temperature`.
+/// If the track has `lat` and `lon`, `distance` (meters) and `speed` (meters per second,
+/// unless the track has its own) are derived from them.
+/// Times are made relative to the first sample.
+#[derive(Debug, Default)]
+pub struct Telemetry {
+    fields: Vec<Field>,
+}
+
+#[derive(Debug)]
+struct Field {
+    name: String,
+    /// Time and value, sorted by time
+    samples: Vec<(f64, f64)>,
+}
+
+impl Telemetry {
+    /// Parse GPX or CSV, GPX is detected by a leading `<`
+    #[allow(clippy::missing_errors_doc)]
+    pub fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        let text = String::from_utf8_lossy(data);
+        let text = text.trim_start_matches('\u{feff}').trim_start();
+        let (names, rows) = if text.starts_with('<') {
+            parse_gpx(text)?
+        } else {
+            parse_csv(text)?
+        };
+        Ok(Self::from_rows(names, rows))
+    }
+
+    fn from_rows(mut names: Vec<String>, mut rows: Vec<(f64, Vec<Option<f64>>)>) -> Self {
+        rows.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if let Some(start) = rows.first().map(|(time, _)| *time) {
+            for (time, _) in &mut rows {
+                *time -= start;
+            }
+        }
+
+        let index = |names: &[String], name: &str| names.iter().position(|n| n == name);
+        if let (Some(lat), Some(lon)) = (index(&names, "lat"), index(&names, "lon")) {
+            let has_speed = index(&names, "speed").is_some();
+            let mut distance = 0.0;
+            let mut previous: Option<(f64, f64, f64)> = None;
+            let mut derived = Vec::with_capacity(rows.len());
+            for (time, values) in &rows {
+                let (Some(lat), Some(lon)) = (values[lat], values[lon]) else {
+                    derived.push((None, None));
+                    continue;
+                };
+                let mut speed = None;
+                if let Some((previous_time, previous_lat, previous_lon)) = previous {
+                    let step = haversine(previous_lat, previous_lon, lat, lon);
+                    distance += step;
+                    if *time > previous_time {
+                        speed = Some(step / (time - previous_time));
+                    }
+                }
+                previous = Some((*time, lat, lon));
+                derived.push((Some(distance), speed));
+            }
+            names.push(String::from("distance"));
+            if !has_speed {
+                names.push(String::from("speed"));
+            }
+            for ((_, values), (distance, speed)) in rows.iter_mut().zip(derived) {
+                values.push(distance);
+                if !has_speed {
+                    values.push(speed);
+                }
+            }
+        }
+
+        let fields = names
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| Field {
+                name,
+                samples: rows
+                    .iter()
+                    .filter_map(|(time, values)| values[i].map(|value| (*time, value)))
+                    .collect(),
+            })
+            .filter(|field| !field.samples.is_empty())
+            .collect();
+        Self { fields }
+    }
+
+    /// Latitude and longitude of samples that have both
+    fn route_points(&self) -> Vec<(f64, f64)> {
+        let (Some(lat), Some(lon)) = (self.field("lat"), self.field("lon")) else {
+            return Vec::new();
+        };
+        self.fields[lat]
+            .samples
+            .iter()
+            .map(|(time, lat)| (*lat, self.value_at(lon, *time)))
+            .collect()
+    }
+
+    fn field(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field.name == name)
+    }
+
+    /// Value of a field at `time`, interpolated between samples
+    fn value_at(&self, field: usize, time: f64) -> f64 {
+        let samples = &self.fields[field].samples;
+        let next = samples.partition_point(|(sample_time, _)| *sample_time <= time);
+        match next {
+            0 => samples[0].1,
+            next if next == samples.len() => samples[next - 1].1,
+            next => {
+                let (from_time, from) = samples[next - 1];
+                let (to_time, to) = samples[next];
+                from + (to - from) * (time - from_time) / (to_time - from_time)
+            }
+        }
+    }
+
+    /// Custom properties for all fields at `time`, e.g. `--webvfx-telemetry-speed: 12.5;`
+    pub fn css_declarations(&self, time: f64) -> String {
+        let mut css = String::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            let _ = write!(
+                css,
+                "{WEBVFX_CSS_TELEMETRY_PREFIX}{}: {}; ",
+                field.name,
+                self.value_at(i, time)
+            );
+        }
+        css
+    }
+}
+
+/// Field names are lowercased with other characters than letters and digits replaced by `-`
+fn field_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .trim_matches('"')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    match name.as_str() {
+        "latitude" => String::from("lat"),
+        "longitude" | "lng" => String::from("lon"),
+        "ele" | "elevation" => String::from("altitude"),
+        "hr" => String::from("heart-rate"),
+        "cad" => String::from("cadence"),
+        "atemp" => String::from("temperature"),
+        _ => name,
+    }
+}
+
+type Rows = (Vec<String>, Vec<(f64, Vec<Option<f64>>)>);
+
+fn parse_csv(text: &str) -> anyhow::Result<Rows> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<String> = lines
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("missing CSV header"))?
+        .split(',')
+        .map(field_name)
+        .collect();
+    let time_column = header
+        .iter()
+        .position(|name| name == "time" || name == "timestamp")
+        .ok_or_else(|| anyhow::anyhow!("missing CSV time column"))?;
+    let names = header
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != time_column)
+        .map(|(_, name)| name.clone())
+        .collect();
+    let rows = lines
+        .enumerate()
+        .map(|(line, row)| {
+            let columns: Vec<&str> = row.split(',').map(|v| v.trim().trim_matches('"')).collect();
+            let time = columns
+                .get(time_column)
+                .and_then(|time| parse_time(time))
+                .ok_or_else(|| anyhow::anyhow!("invalid time in CSV row {}", line + 2))?;
+            let values = (0..header.len())
+                .filter(|i| *i != time_column)
+                .map(|i| columns.get(i).and_then(|value| value.parse().ok()))
+                .collect();
+            Ok((time, values))
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok((names, rows))
+}
+
+fn parse_gpx(text: &str) -> anyhow::Result<Rows> {
+    let document = roxmltree::Document::parse(text)?;
+    let mut names: Vec<String> = vec![String::from("lat"), String::from("lon")];
+    let mut rows = Vec::new();
+    for point in document
+        .descendants()
+        .filter(|node| node.has_tag_name("trkpt"))
+    {
+        let Some(time) = point
+            .children()
+            .find(|node| node.has_tag_name("time"))
+            .and_then(|node| node.text())
+            .and_then(parse_time)
+        else {
+            continue;
+        };
+        let mut values = vec![
+            point.attribute("lat").and_then(|lat| lat.parse().ok()),
+            point.attribute("lon").and_then(|lon| lon.parse().ok()),
+        ];
+        // Elevation and extension values such as <gpxtpx:hr>
+        for node in point.descendants().filter(|node| {
+            node.is_element()
+                && !node.has_tag_name("time")
+                && !node.children().any(|child| child.is_element())
+        }) {
+            let Some(value) = node.text().and_then(|text| text.trim().parse().ok()) else {
+                continue;
+            };
+            let name = field_name(node.tag_name().name());
+            let index = names.iter().position(|n| *n == name).unwrap_or_else(|| {
+                names.push(name);
+                names.len() - 1
+            });
+            values.resize(names.len(), None);
+            values[index] = Some(value);
+        }
+        rows.push((time, values));
+    }
+    if rows.is_empty() {
+        return Err(anyhow::anyhow!("no timed track points in GPX"));
+    }
+    for (_, values) in &mut rows {
+        values.resize(names.len(), None);
+    }
+    Ok((names, rows))
+}
+
+/// Parse seconds or an RFC 3339 date-time such as `2024-05-01T10:00:00.5Z` into seconds
+pub(super) fn parse_time(time: &str) -> Option<f64> {
+    let time = time.trim();
+    if let Ok(seconds) = time.parse() {
+        return Some(seconds);
+    }
+    let (date, time) = time.split_once(['T', 't', ' '])?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+
+    let (time, offset) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+        (time, 0.0)
+    } else if let Some(sign_index) = time.rfind(['+', '-']) {
+        let (time, offset) = time.split_at(sign_index);
+        let sign = if offset.starts_with('-') { -1.0 } else { 1.0 };
+        let (hours, minutes) = offset[1..].split_once(':')?;
+        let offset = hours.parse::<f64>().ok()? * 3600.0 + minutes.parse::<f64>().ok()? * 60.0;
+        (time, sign * offset)
+    } else {
+        (time, 0.0)
+    };
+    let mut time = time.splitn(3, ':');
+    let hours: f64 = time.next()?.parse().ok()?;
+    let minutes: f64 = time.next()?.parse().ok()?;
+    let seconds: f64 = time.next()?.parse().ok()?;
+
+    #[allow(clippy::cast_precision_loss)]
+    let days = days_from_civil(year, month, day) as f64;
+    Some(days * 86400.0 + hours * 3600.0 + minutes * 60.0 + seconds - offset)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Distance in meters between two coordinates in degrees
+fn haversine(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (lon2 - lon1).to_radians();
+    let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// Projects coordinates into the SVG user space of the route
+struct Projection {
+    min: (f64, f64),
+    scale: f64,
+    cos_lat: f64,
+    size: (f64, f64),
+}
+
+impl Projection {
+    #[allow(clippy::cast_precision_loss)]
+    fn new(points: &[(f64, f64)]) -> Option<Self> {
+        if points.is_empty() {
+            return None;
+        }
+        let mean_lat = points.iter().map(|(lat, _)| lat).sum::<f64>() / points.len() as f64;
+        let cos_lat = mean_lat.to_radians().cos();
+        let project = |(lat, lon): (f64, f64)| (lon * cos_lat, -lat);
+        let (mut min, mut max) = ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN));
+        for (x, y) in points.iter().copied().map(project) {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        let extent = (max.0 - min.0).max(max.1 - min.1);
+        if !extent.is_finite() {
+            return None;
+        }
+        let scale = if extent > 0.0 {
+            ROUTE_SIZE / extent
+        } else {
+            1.0
+        };
+        Some(Self {
+            min,
+            scale,
+            cos_lat,
+            size: (
+                (max.0 - min.0) * scale + ROUTE_PADDING * 2.0,
+                (max.1 - min.1) * scale + ROUTE_PADDING * 2.0,
+            ),
+        })
+    }
+
+    fn project(&self, (lat, lon): (f64, f64)) -> (f64, f64) {
+        (
+            (lon * self.cos_lat - self.min.0) * self.scale + ROUTE_PADDING,
+            (-lat - self.min.1) * self.scale + ROUTE_PADDING,
+        )
+    }
+}
+
+/// A `.webvfx-route` element, its generated route image and marker
+struct Route {
+    path_id: usize,
+    marker_id: usize,
+}
+
+/// Applies telemetry to a document: custom properties on the root element,
+/// the text of `data-webvfx-field` elements and the marker of `.webvfx-route` elements.
+///
+/// Route elements get an `img.webvfx-route-path` child showing the GPS route scaled to fit,
+/// and a `.webvfx-route-marker` child positioned at the current location.
+/// The route stroke is set with `data-webvfx-route-stroke` and `data-webvfx-route-stroke-width`
+/// attributes, the route is scaled so its longest side is 1000 units.
+pub struct TelemetryPlayer {
+    telemetry: Telemetry,
+    projection: Option<Projection>,
+    /// Field elements, the field index, precision and scale
+    fields: Vec<(usize, usize, usize, f64)>,
+    routes: Vec<Route>,
+    applied: HashMap<usize, String>,
+}
+
+impl TelemetryPlayer {
+    pub fn new(telemetry: Telemetry, document: &mut BaseDocument) -> Self {
+        let mut player = Self {
+            projection: Projection::new(&telemetry.route_points()),
+            telemetry,
+            fields: Vec::new(),
+            routes: Vec::new(),
+            applied: HashMap::new(),
+        };
+        player.bind(document);
+        player
+    }
+
+    /// Find the field and route elements in a new document, and add route images to it
+    pub fn bind(&mut self, document: &mut BaseDocument) {
+        self.applied.clear();
+        let attr = |node_id: usize, name: &str| {
+            document
+                .get_node(node_id)
+                .and_then(|node| node.attr(LocalName::from(name)))
+                .map(String::from)
+        };
+        self.fields = document
+            .query_selector_all(WEBVFX_FIELD_SELECTOR)
+            .unwrap_or_default()
+            .iter()
+            .copied()
+            .filter_map(|node_id| {
+                let name = attr(node_id, WEBVFX_FIELD_ATTRIBUTE)?;
+                let Some(field) = self.telemetry.field(&field_name(&name)) else {
+                    log::warn!("telemetry field '{name}' not found");
+                    return None;
+                };
+                let precision = attr(node_id, WEBVFX_PRECISION_ATTRIBUTE)
+                    .and_then(|precision| precision.trim().parse().ok())
+                    .unwrap_or(0);
+                let scale = attr(node_id, WEBVFX_SCALE_ATTRIBUTE)
+                    .and_then(|scale| scale.trim().parse().ok())
+                    .unwrap_or(1.0);
+                Some((node_id, field, precision, scale))
+            })
+            .collect();
+
+        self.routes = Vec::new();
+        let Some(projection) = &self.projection else {
+            return;
+        };
+        let route_points = self.telemetry.route_points();
+        let route_svgs: Vec<(usize, String)> = document
+            .query_selector_all(WEBVFX_ROUTE_SELECTOR)
+            .unwrap_or_default()
+            .iter()
+            .map(|route_id| {
+                let stroke = attr(*route_id, WEBVFX_ROUTE_STROKE_ATTRIBUTE)
+                    .unwrap_or_else(|| String::from("white"));
+                let stroke_width = attr(*route_id, WEBVFX_ROUTE_STROKE_WIDTH_ATTRIBUTE)
+                    .unwrap_or_else(|| String::from("8"));
+                (
+                    *route_id,
+                    route_svg(projection, &route_points, &stroke, &stroke_width),
+                )
+            })
+            .collect();
+        for (route_id, svg) in route_svgs {
+            let class_attribute = |class: &str| Attribute {
+                name: QualName::new(None, ns!(), local_name!("class")),
+                value: class.to_string(),
+            };
+            let mut mutator = document.mutate();
+            let path_id = mutator.create_element(
+                QualName::new(None, ns!(html), local_name!("img")),
+                vec![
+                    class_attribute(WEBVFX_ROUTE_PATH_CLASS),
+                    Attribute {
+                        name: QualName::new(None, ns!(), local_name!("src")),
+                        value: format!("data:image/svg+xml,{}", percent_encode(&svg)),
+                    },
+                ],
+            );
+            let marker_id = mutator.create_element(
+                QualName::new(None, ns!(html), local_name!("div")),
+                vec![class_attribute(WEBVFX_ROUTE_MARKER_CLASS)],
+            );
+            mutator.append_children(route_id, &[path_id, marker_id]);
+            self.routes.push(Route { path_id, marker_id });
+        }
+    }
+
+    /// Remove the route images added to the document by `bind`
+    pub fn unbind(&mut self, document: &mut BaseDocument) {
+        let mut mutator = document.mutate();
+        for route in self.routes.drain(..) {
+            mutator.remove_and_drop_node(route.path_id);
+            mutator.remove_and_drop_node(route.marker_id);
+        }
+    }
+
+    /// Apply the telemetry at `time`, field text is only written when it changes.
+    /// Markers are positioned using the layout of the previous frame.
+    pub fn apply(
+        &mut self,
+        time: f64,
+        document: &mut BaseDocument,
+        inline_styles: &mut InlineStyles,
+    ) {
+        let root_id = document.root_element().id;
+        inline_styles.push(root_id, &self.telemetry.css_declarations(time));
+
+        for &(node_id, field, precision, scale) in &self.fields {
+            let value = self.telemetry.value_at(field, time) * scale;
+            let text = format!("{value:.precision$}");
+            if self.applied.get(&node_id) != Some(&text) {
+                set_text(document, node_id, &text);
+                self.applied.insert(node_id, text);
+            }
+        }
+
+        let (Some(projection), Some(lat), Some(lon)) = (
+            &self.projection,
+            self.telemetry.field("lat"),
+            self.telemetry.field("lon"),
+        ) else {
+            return;
+        };
+        let (x, y) = projection.project((
+            self.telemetry.value_at(lat, time),
+            self.telemetry.value_at(lon, time),
+        ));
+        for route in &self.routes {
+            let Some(size) = document
+                .get_node(route.path_id)
+                .map(|node| node.final_layout.size)
+            else {
+                continue;
+            };
+            // The image is scaled to fit its box, centered
+            let (width, height) = (f64::from(size.width), f64::from(size.height));
+            let scale = (width / projection.size.0).min(height / projection.size.1);
+            let left = (width - projection.size.0 * scale) / 2.0 + x * scale;
+            let top = (height - projection.size.1 * scale) / 2.0 + y * scale;
+            inline_styles.push(route.marker_id, &format!("left: {left}px; top: {top}px;"));
+        }
+    }
+}
+
+fn route_svg(
+    projection: &Projection,
+    points: &[(f64, f64)],
+    stroke: &str,
+    stroke_width: &str,
+) -> String {
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}"><polyline fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round" stroke-linecap="round" points=""#,
+        projection.size.0,
+        projection.size.1,
+        escape_attribute(stroke),
+        escape_attribute(stroke_width),
+    );
+    for point in points {
+        let (x, y) = projection.project(*point);
+        let _ = write!(svg, "{x:.1},{y:.1} ");
+    }
+    svg.push_str(r#""/></svg>"#);
+    svg
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~=:/,".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("12.5"), Some(12.5));
+        assert_eq!(parse_time("1970-01-02T00:00:01Z"), Some(86401.0));
+        assert_eq!(
+            parse_time("2024-05-01T10:00:00.5+02:00"),
+            Some(1_714_550_400.5)
+        );
+        assert_eq!(parse_time("bogus"), None);
+    }
+
+    #[test]
+    fn test_csv() {
+        let telemetry =
+            Telemetry::parse(b"Time,Speed,Heart Rate\n10,2,100\n12,4,\n14,8,120\n").unwrap();
+        let speed = telemetry.field("speed").unwrap();
+        let heart_rate = telemetry.field("heart-rate").unwrap();
+        assert!((telemetry.value_at(speed, -1.0) - 2.0).abs() < 1e-9);
+        assert!((telemetry.value_at(speed, 1.0) - 3.0).abs() < 1e-9);
+        assert!((telemetry.value_at(heart_rate, 1.0) - 105.0).abs() < 1e-9);
+        assert!((telemetry.value_at(speed, 10.0) - 8.0).abs() < 1e-9);
+        assert!(Telemetry::parse(b"speed\n1\n").is_err());
+    }
+
+    #[test]
+    fn test_gpx() {
+        let telemetry = Telemetry::parse(
+            br#"<?xml version="1.0"?>
+            <gpx xmlns="http://www.topografix.com/GPX/1/1"
+                xmlns:gpxtpx="http://www.garmin.com/xmlschemas/TrackPointExtension/v1">
+              <trk><trkseg>
+                <trkpt lat="0" lon="0"><ele>10</ele><time>2024-05-01T10:00:00Z</time>
+                  <extensions><gpxtpx:TrackPointExtension><gpxtpx:hr>90</gpxtpx:hr></gpxtpx:TrackPointExtension></extensions>
+                </trkpt>
+                <trkpt lat="0" lon="0.001"><ele>20</ele><time>2024-05-01T10:00:10Z</time></trkpt>
+              </trkseg></trk>
+            </gpx>"#,
+        )
+        .unwrap();
+        let altitude = telemetry.field("altitude").unwrap();
+        let distance = telemetry.field("distance").unwrap();
+        let speed = telemetry.field("speed").unwrap();
+        assert!((telemetry.value_at(altitude, 5.0) - 15.0).abs() < 1e-9);
+        assert!((telemetry.value_at(distance, 10.0) - 111.19).abs() < 0.01);
+        assert!((telemetry.value_at(speed, 10.0) - 11.119).abs() < 0.001);
+        assert!(telemetry.field("heart-rate").is_some());
+        assert_eq!(telemetry.route_points().len(), 2);
+    }
+}
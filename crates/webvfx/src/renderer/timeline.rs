@@ -0,0 +1,364 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::{BTreeMap, HashMap};
+
+use blitz_dom::{BaseDocument, LocalName, Node, QualName, ns};
+use serde::{Deserialize, Deserializer};
+use smallvec::SmallVec;
+
+use super::{easing::Easing, style::InlineStyles};
+
+pub const WEBVFX_TIMELINE_LINK_SELECTOR: &str = r#"link[rel="webvfx-timeline"]"#;
+
+/// Keyframed inline styles, attributes and text content of elements.
+/// Timelines are JSON only, e.g.
+/// ```json
+/// {
+///   "tracks": [
+///     {
+///       "selector": "#title",
+///       "styles": {
+///         "opacity": [{ "time": 0, "value": 0, "easing": "ease-out" }, { "time": 1, "value": 1 }]
+///       },
+///       "attributes": { "width": [{ "time": 0, "value": 100 }, { "time": 2, "value": 200 }] },
+///       "text": [{ "time": 0, "value": "0 km" }, { "time": 5, "value": "42 km" }]
+///     }
+///   ]
+/// }
+/// ```
+/// Times are in seconds. Between keyframes, values whose text only differs in numbers
+/// are interpolated (e.g. `10px` to `20px`, `rgb(0, 0, 0)` to `rgb(255, 0, 0)`)
+/// with up to 3 decimal places,
+/// other values change at the next keyframe. The easing of a keyframe applies until the next one.
+#[derive(Debug, Deserialize)]
+pub struct Timeline {
+    tracks: Vec<Track>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Track {
+    selector: String,
+    #[serde(default)]
+    styles: BTreeMap<String, Vec<Keyframe>>,
+    #[serde(default)]
+    attributes: BTreeMap<String, Vec<Keyframe>>,
+    #[serde(default)]
+    text: Vec<Keyframe>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Keyframe {
+    time: f64,
+    #[serde(deserialize_with = "deserialize_value")]
+    value: String,
+    #[serde(default, deserialize_with = "deserialize_easing")]
+    easing: Easing,
+}
+
+fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(value) => Ok(value),
+        serde_json::Value::Number(value) => Ok(value.to_string()),
+        _ => Err(serde::de::Error::custom("value must be a string or number")),
+    }
+}
+
+fn deserialize_easing<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Easing, D::Error> {
+    Easing::parse(&String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+}
+
+impl Timeline {
+    #[allow(clippy::missing_errors_doc)]
+    pub fn parse(json: &[u8]) -> anyhow::Result<Self> {
+        let mut timeline: Self = serde_json::from_slice(json)?;
+        for track in &mut timeline.tracks {
+            for keyframes in track
+                .styles
+                .values_mut()
+                .chain(track.attributes.values_mut())
+                .chain(std::iter::once(&mut track.text))
+            {
+                keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+            }
+        }
+        Ok(timeline)
+    }
+}
+
+/// Key of a value written to the document by the timeline
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Target {
+    Attribute(usize, String),
+    Text(usize),
+}
+
+/// Applies a timeline to the elements matching its selectors in a document
+pub struct TimelinePlayer {
+    timeline: Timeline,
+    nodes: Vec<SmallVec<[usize; 32]>>,
+    applied: HashMap<Target, String>,
+}
+
+impl TimelinePlayer {
+    pub fn new(timeline: Timeline, document: &BaseDocument) -> Self {
+        let mut player = Self {
+            timeline,
+            nodes: Vec::new(),
+            applied: HashMap::new(),
+        };
+        player.bind(document);
+        player
+    }
+
+    /// Resolve the track selectors against a new document
+    pub fn bind(&mut self, document: &BaseDocument) {
+        self.applied.clear();
+        self.nodes = self
+            .timeline
+            .tracks
+            .iter()
+            .map(|track| match document.query_selector_all(&track.selector) {
+                Ok(node_ids) => node_ids,
+                Err(e) => {
+                    log::warn!("invalid timeline selector '{}': {e:?}", track.selector);
+                    SmallVec::new()
+                }
+            })
+            .collect();
+    }
+
+    /// Apply the timeline at `time`, styles are pushed to `inline_styles`.
+    /// Attributes and text are only written when their value changes.
+    pub fn apply(
+        &mut self,
+        time: f64,
+        document: &mut BaseDocument,
+        inline_styles: &mut InlineStyles,
+    ) {
+        for (track, node_ids) in self.timeline.tracks.iter().zip(&self.nodes) {
+            let styles: String = track
+                .styles
+                .iter()
+                .filter_map(|(name, keyframes)| {
+                    value_at(keyframes, time).map(|value| format!("{name}: {value};"))
+                })
+                .collect();
+            let attributes: Vec<(&String, String)> = track
+                .attributes
+                .iter()
+                .filter_map(|(name, keyframes)| value_at(keyframes, time).map(|v| (name, v)))
+                .collect();
+            let text = value_at(&track.text, time);
+
+            for node_id in node_ids.iter().copied() {
+                if !styles.is_empty() {
+                    inline_styles.push(node_id, &styles);
+                }
+                for (name, value) in &attributes {
+                    let target = Target::Attribute(node_id, (*name).clone());
+                    if self.applied.get(&target) != Some(value) {
+                        document.mutate().set_attribute(
+                            node_id,
+                            QualName::new(None, ns!(), LocalName::from(name.as_str())),
+                            value,
+                        );
+                        self.applied.insert(target, value.clone());
+                    }
+                }
+                if let Some(text) = &text
+                    && self.applied.get(&Target::Text(node_id)) != Some(text)
+                {
+                    set_text(document, node_id, text);
+                    self.applied.insert(Target::Text(node_id), text.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Replace the first text child of the node, or append one
+pub(super) fn set_text(document: &mut BaseDocument, node_id: usize, text: &str) {
+    let text_node_id = document.get_node(node_id).and_then(|node| {
+        node.children
+            .iter()
+            .copied()
+            .find(|child_id| document.get_node(*child_id).is_some_and(Node::is_text_node))
+    });
+    let mut mutator = document.mutate();
+    match text_node_id {
+        Some(text_node_id) => mutator.set_node_text(text_node_id, text),
+        None => {
+            let text_node_id = mutator.create_text_node(text);
+            mutator.append_children(node_id, &[text_node_id]);
+        }
+    }
+}
+
+/// Replace all the children of the node with a text node, like setting `textContent`
+pub(super) fn set_text_content(document: &mut BaseDocument, node_id: usize, text: &str) {
+    let children = document
+        .get_node(node_id)
+        .map(|node| node.children.clone())
+        .unwrap_or_default();
+    if let [child_id] = children[..]
+        && document.get_node(child_id).is_some_and(Node::is_text_node)
+    {
+        document.mutate().set_node_text(child_id, text);
+        return;
+    }
+    let mut mutator = document.mutate();
+    for child_id in children {
+        mutator.remove_and_drop_node(child_id);
+    }
+    let text_node_id = mutator.create_text_node(text);
+    mutator.append_children(node_id, &[text_node_id]);
+}
+
+fn value_at(keyframes: &[Keyframe], time: f64) -> Option<String> {
+    let next = keyframes.iter().position(|keyframe| keyframe.time > time);
+    match next {
+        None => keyframes.last().map(|keyframe| keyframe.value.clone()),
+        Some(0) => keyframes.first().map(|keyframe| keyframe.value.clone()),
+        Some(next) => {
+            let from = &keyframes[next - 1];
+            let to = &keyframes[next];
+            let t = from
+                .easing
+                .apply((time - from.time) / (to.time - from.time));
+            Some(interpolate(&from.value, &to.value, t))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Text(&'a str),
+    Number(&'a str),
+}
+
+/// Split into numbers and the text between them.
+/// Digits directly following a letter or `#` are text, so `h1` and `#ff0000` are not numbers.
+fn tokenize(value: &str) -> Vec<Token<'_>> {
+    let bytes = value.as_bytes();
+    let mut tokens = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let starts_number = {
+            let mut j = i;
+            if bytes[j] == b'-' {
+                j += 1;
+            }
+            if j < bytes.len() && bytes[j] == b'.' {
+                j += 1;
+            }
+            j < bytes.len()
+                && bytes[j].is_ascii_digit()
+                && (i == 0 || !(bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'#'))
+        };
+        if starts_number {
+            let mut end = i + 1;
+            while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+                end += 1;
+            }
+            if text_start < i {
+                tokens.push(Token::Text(&value[text_start..i]));
+            }
+            tokens.push(Token::Number(&value[i..end]));
+            text_start = end;
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    if text_start < bytes.len() {
+        tokens.push(Token::Text(&value[text_start..]));
+    }
+    tokens
+}
+
+/// Interpolate the numbers of values with the same text, otherwise step to `to` at the end
+fn interpolate(from: &str, to: &str, t: f64) -> String {
+    let from_tokens = tokenize(from);
+    let to_tokens = tokenize(to);
+    let compatible = from_tokens.len() == to_tokens.len()
+        && from_tokens.iter().zip(&to_tokens).all(|pair| match pair {
+            (Token::Text(a), Token::Text(b)) => a == b,
+            (Token::Number(a), Token::Number(b)) => {
+                a.parse::<f64>().is_ok() && b.parse::<f64>().is_ok()
+            }
+            _ => false,
+        });
+    if !compatible {
+        return String::from(if t < 1.0 { from } else { to });
+    }
+    from_tokens
+        .iter()
+        .zip(&to_tokens)
+        .map(|pair| match pair {
+            (Token::Number(a), Token::Number(b)) => {
+                let (a_value, b_value) = (a.parse::<f64>().unwrap(), b.parse::<f64>().unwrap());
+                let value = format!("{:.3}", a_value + (b_value - a_value) * t);
+                let value = value.trim_end_matches('0').trim_end_matches('.');
+                // Avoid "-0"
+                String::from(if value == "-0" { "0" } else { value })
+            }
+            (Token::Text(text), _) => (*text).to_string(),
+            (Token::Number(_), Token::Text(_)) => unreachable!(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolate() {
+        assert_eq!(interpolate("10px", "20px", 0.5), "15px");
+        assert_eq!(
+            interpolate("translate(0px, -10.5px)", "translate(100px, 10.5px)", 0.25),
+            "translate(25px, -5.25px)"
+        );
+        assert_eq!(interpolate("0 km", "42 km", 0.5), "21 km");
+        assert_eq!(interpolate("0 km", "42 km", 0.25), "10.5 km");
+        assert_eq!(interpolate("-1", "1", 0.5), "0");
+        assert_eq!(interpolate("10", "20", 1.0), "20");
+        assert_eq!(interpolate("#000000", "#ff0000", 0.5), "#000000");
+        assert_eq!(interpolate("Hello", "World", 1.0), "World");
+    }
+
+    #[test]
+    fn test_value_at() {
+        let timeline = Timeline::parse(
+            br#"{"tracks": [{"selector": "p", "text": [
+                {"time": 2, "value": 100},
+                {"time": 0, "value": 0, "easing": "linear"}
+            ]}]}"#,
+        )
+        .unwrap();
+        let keyframes = &timeline.tracks[0].text;
+        assert_eq!(value_at(keyframes, -1.0).unwrap(), "0");
+        assert_eq!(value_at(keyframes, 0.5).unwrap(), "25");
+        assert_eq!(value_at(keyframes, 3.0).unwrap(), "100");
+    }
+
+    #[test]
+    fn test_opacity() {
+        // The example from the `Timeline` docs
+        let timeline = Timeline::parse(
+            br##"{"tracks": [{"selector": "#title", "styles": {
+                "opacity": [{ "time": 0, "value": 0, "easing": "ease-out" }, { "time": 1, "value": 1 }]
+            }}]}"##,
+        )
+        .unwrap();
+        let keyframes = &timeline.tracks[0].styles["opacity"];
+        let opacity = |time| value_at(keyframes, time).unwrap().parse::<f64>().unwrap();
+        assert!(opacity(0.0).abs() < f64::EPSILON);
+        assert!(opacity(0.25) > 0.25 && opacity(0.25) < 0.5);
+        assert!(opacity(0.5) > 0.5 && opacity(0.5) < 1.0);
+        assert!(opacity(0.75) > opacity(0.5) && opacity(0.75) < 1.0);
+        assert!((opacity(1.0) - 1.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,311 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Write;
+
+use super::easing::Easing;
+
+pub const WEBVFX_CSS_TIME_PROPERTY: &str = "--webvfx-time";
+pub const WEBVFX_CSS_PROGRESS_PROPERTY: &str = "--webvfx-progress";
+pub const WEBVFX_CSS_FRAME_PROPERTY: &str = "--webvfx-frame";
+pub const WEBVFX_CSS_CLIP_DURATION_PROPERTY: &str = "--webvfx-clip-duration";
+pub const WEBVFX_CSS_TRANSITION_PROPERTY: &str = "--webvfx-transition";
+pub const WEBVFX_CSS_IN_DURATION_PROPERTY: &str = "--webvfx-in-duration";
+pub const WEBVFX_CSS_IN_DELAY_PROPERTY: &str = "--webvfx-in-delay";
+pub const WEBVFX_CSS_IN_PROGRESS_PROPERTY: &str = "--webvfx-in-progress";
+pub const WEBVFX_CSS_OUT_DURATION_PROPERTY: &str = "--webvfx-out-duration";
+pub const WEBVFX_CSS_OUT_DELAY_PROPERTY: &str = "--webvfx-out-delay";
+pub const WEBVFX_CSS_OUT_PROGRESS_PROPERTY: &str = "--webvfx-out-progress";
+
+pub const DEFAULT_FRAME_RATE: f64 = 25.0;
+
+/// Host supplied information about the clip the effect is applied to
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeParams {
+    /// Clip duration in seconds, if 0 the CSS animation duration is used
+    pub clip_duration: f64,
+    pub frame_rate: f64,
+    /// Transition position in the range 0..1, set by hosts running the effect as a transition
+    pub transition: Option<f64>,
+    pub easing: Easing,
+    /// Drive CSS animations from the eased transition position instead of time
+    pub scrub_animations: bool,
+    /// Document time in seconds at the start of the clip
+    pub time_offset: f64,
+    /// Multiplier applied to time
+    pub time_speed: f64,
+    /// If greater than 0, time loops over this many seconds
+    pub loop_duration: f64,
+    pub time_direction: TimeDirection,
+    /// Duration in seconds of the intro at the start of the clip
+    pub in_duration: f64,
+    /// Duration in seconds of the outro at the end of the clip
+    pub out_duration: f64,
+    /// Seconds added to document time to look up telemetry samples
+    pub telemetry_offset: f64,
+}
+
+impl Default for TimeParams {
+    fn default() -> Self {
+        Self {
+            clip_duration: 0.0,
+            frame_rate: DEFAULT_FRAME_RATE,
+            transition: None,
+            easing: Easing::Linear,
+            scrub_animations: false,
+            time_offset: 0.0,
+            time_speed: 1.0,
+            loop_duration: 0.0,
+            time_direction: TimeDirection::Forward,
+            in_duration: 0.0,
+            out_duration: 0.0,
+            telemetry_offset: 0.0,
+        }
+    }
+}
+
+impl TimeParams {
+    /// Clip duration in seconds, falling back to `animation_duration`
+    pub fn clip_duration(&self, animation_duration: Option<f64>) -> Option<f64> {
+        if self.clip_duration > 0.0 {
+            Some(self.clip_duration)
+        } else {
+            animation_duration.filter(|duration| *duration > 0.0)
+        }
+    }
+
+    /// The eased transition position
+    pub fn transition(&self) -> Option<f64> {
+        self.transition.map(|position| self.easing.apply(position))
+    }
+
+    /// Time in seconds to resolve the document at
+    pub fn document_time(&self, time: f64, animation_duration: Option<f64>) -> f64 {
+        match (self.scrub_animations, self.transition(), animation_duration) {
+            (true, Some(transition), Some(animation_duration)) => transition * animation_duration,
+            _ => self.remap(time, animation_duration),
+        }
+    }
+
+    /// Apply offset, speed, looping and direction to `time`.
+    /// Reverse and ping-pong play over the loop duration, or the clip duration if not looping.
+    fn remap(&self, time: f64, animation_duration: Option<f64>) -> f64 {
+        let time = self.time_offset + time * self.time_speed;
+        let looping = self.loop_duration > 0.0;
+        let length = if looping {
+            Some(self.loop_duration)
+        } else {
+            self.clip_duration(animation_duration)
+        };
+        match (self.time_direction, length) {
+            (TimeDirection::Forward, Some(length)) if looping => time.rem_euclid(length),
+            (TimeDirection::Reverse, Some(length)) => {
+                if looping {
+                    length - time.rem_euclid(length)
+                } else {
+                    length - time.clamp(0.0, length)
+                }
+            }
+            (TimeDirection::PingPong, Some(length)) => {
+                let time = time.rem_euclid(length * 2.0);
+                if time > length {
+                    length * 2.0 - time
+                } else {
+                    time
+                }
+            }
+            _ => time,
+        }
+    }
+
+    /// Time, progress and frame number custom properties for `time` in seconds.
+    /// Time and clip duration are unitless seconds, use e.g. `calc(var(--webvfx-time) * 1s)`.
+    /// Time is the remapped document time, progress and frame are relative to the clip.
+    pub fn css_declarations(&self, time: f64, animation_duration: Option<f64>) -> String {
+        let document_time = self.document_time(time, animation_duration);
+        let clip_duration = self.clip_duration(animation_duration);
+        let progress = clip_duration.map_or(0.0, |duration| (time / duration).clamp(0.0, 1.0));
+        let frame = if self.frame_rate > 0.0 {
+            (time * self.frame_rate).round()
+        } else {
+            0.0
+        };
+        let mut css = format!(
+            "{WEBVFX_CSS_TIME_PROPERTY}: {document_time}; {WEBVFX_CSS_PROGRESS_PROPERTY}: {progress}; {WEBVFX_CSS_FRAME_PROPERTY}: {frame};"
+        );
+        if let Some(clip_duration) = clip_duration {
+            let _ = write!(
+                css,
+                " {WEBVFX_CSS_CLIP_DURATION_PROPERTY}: {clip_duration};"
+            );
+        }
+        if let Some(transition) = self.transition() {
+            let _ = write!(css, " {WEBVFX_CSS_TRANSITION_PROPERTY}: {transition};");
+        }
+        self.write_in_out_css(&mut css, time, document_time, clip_duration);
+        css
+    }
+
+    /// Intro and outro durations and delays are CSS `<time>` values so they can be used
+    /// directly in `animation` shorthands, the outro delay makes an outro animation end at
+    /// the clip end: `animation: outro var(--webvfx-out-duration) var(--webvfx-out-delay) both`.
+    /// Progress is relative to the clip, animations run on document time so the delays are
+    /// offset from the clip time by the document time, keeping them in step when time is remapped.
+    fn write_in_out_css(
+        &self,
+        css: &mut String,
+        time: f64,
+        document_time: f64,
+        clip_duration: Option<f64>,
+    ) {
+        let offset = document_time - time;
+        if self.in_duration > 0.0 {
+            let in_progress = (time / self.in_duration).clamp(0.0, 1.0);
+            let _ = write!(
+                css,
+                " {WEBVFX_CSS_IN_DURATION_PROPERTY}: {}s; {WEBVFX_CSS_IN_DELAY_PROPERTY}: {offset}s; {WEBVFX_CSS_IN_PROGRESS_PROPERTY}: {in_progress};",
+                self.in_duration
+            );
+        }
+        if self.out_duration > 0.0
+            && let Some(clip_duration) = clip_duration
+        {
+            let out_duration = self.out_duration.min(clip_duration);
+            let out_start = clip_duration - out_duration;
+            let out_delay = out_start + offset;
+            let out_progress = ((time - out_start) / out_duration).clamp(0.0, 1.0);
+            let _ = write!(
+                css,
+                " {WEBVFX_CSS_OUT_DURATION_PROPERTY}: {out_duration}s; {WEBVFX_CSS_OUT_DELAY_PROPERTY}: {out_delay}s; {WEBVFX_CSS_OUT_PROGRESS_PROPERTY}: {out_progress};"
+            );
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeDirection {
+    #[default]
+    Forward,
+    Reverse,
+    PingPong,
+}
+
+impl TimeDirection {
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        match s.trim() {
+            "" | "forward" => Ok(Self::Forward),
+            "reverse" => Ok(Self::Reverse),
+            "ping-pong" => Ok(Self::PingPong),
+            _ => Err(anyhow::anyhow!("invalid time direction '{s}'")),
+        }
+    }
+}
+
+/// Parse a CSS `<time>` such as `5s` or `500ms` into seconds
+pub fn parse_css_duration(duration: &str) -> Option<f64> {
+    let duration = duration.trim();
+    if let Some(ms) = duration.strip_suffix("ms") {
+        ms.trim().parse::<f64>().ok().map(|ms| ms / 1000.0)
+    } else {
+        duration.strip_suffix('s')?.trim().parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_css_duration() {
+        assert_eq!(parse_css_duration("5s"), Some(5.0));
+        assert_eq!(parse_css_duration(" 500ms"), Some(0.5));
+        assert_eq!(parse_css_duration("5"), None);
+    }
+
+    #[test]
+    fn test_css_declarations() {
+        let time_params = TimeParams {
+            clip_duration: 4.0,
+            frame_rate: 30.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            time_params.css_declarations(1.0, Some(5.0)),
+            "--webvfx-time: 1; --webvfx-progress: 0.25; --webvfx-frame: 30; --webvfx-clip-duration: 4;"
+        );
+        assert_eq!(
+            TimeParams::default().css_declarations(10.0, Some(5.0)),
+            "--webvfx-time: 10; --webvfx-progress: 1; --webvfx-frame: 250; --webvfx-clip-duration: 5;"
+        );
+    }
+
+    #[test]
+    fn test_transition() {
+        let time_params = TimeParams {
+            transition: Some(0.5),
+            scrub_animations: true,
+            ..Default::default()
+        };
+        assert!(
+            time_params
+                .css_declarations(1.0, None)
+                .ends_with("--webvfx-transition: 0.5;")
+        );
+        assert!((time_params.document_time(1.0, Some(4.0)) - 2.0).abs() < 1e-9);
+        assert!((time_params.document_time(1.0, None) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_remap() {
+        let remap = |time_params: TimeParams, time: f64| time_params.document_time(time, None);
+        let looping = TimeParams {
+            loop_duration: 2.0,
+            ..Default::default()
+        };
+        assert!((remap(looping.clone(), 5.0) - 1.0).abs() < 1e-9);
+        let offset = TimeParams {
+            time_offset: 1.0,
+            time_speed: 0.5,
+            ..Default::default()
+        };
+        assert!((remap(offset, 4.0) - 3.0).abs() < 1e-9);
+        let reverse = TimeParams {
+            time_direction: TimeDirection::Reverse,
+            ..looping.clone()
+        };
+        assert!((remap(reverse, 0.5) - 1.5).abs() < 1e-9);
+        let ping_pong = TimeParams {
+            time_direction: TimeDirection::PingPong,
+            ..looping
+        };
+        assert!((remap(ping_pong.clone(), 1.5) - 1.5).abs() < 1e-9);
+        assert!((remap(ping_pong, 2.5) - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_in_out() {
+        let time_params = TimeParams {
+            clip_duration: 10.0,
+            in_duration: 2.0,
+            out_duration: 4.0,
+            ..Default::default()
+        };
+        assert!(time_params.css_declarations(7.0, None).ends_with(
+            "--webvfx-in-duration: 2s; --webvfx-in-delay: 0s; --webvfx-in-progress: 1; --webvfx-out-duration: 4s; --webvfx-out-delay: 6s; --webvfx-out-progress: 0.25;"
+        ));
+    }
+
+    #[test]
+    fn test_in_out_reverse() {
+        let time_params = TimeParams {
+            clip_duration: 10.0,
+            out_duration: 4.0,
+            time_direction: TimeDirection::Reverse,
+            ..Default::default()
+        };
+        // At clip time 7 the document time is 3, an outro animation is 1s into its 4s
+        assert!(time_params.css_declarations(7.0, None).ends_with(
+            "--webvfx-out-duration: 4s; --webvfx-out-delay: 2s; --webvfx-out-progress: 0.25;"
+        ));
+    }
+}
@@ -0,0 +1,56 @@
+// Copyright (C) 2025 Andrew Wason
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Polls file modification times to detect changes to the files a document was loaded from.
+#[derive(Default)]
+pub struct FileWatcher {
+    files: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start watching `path`, its current modification time is the baseline for `changed`
+    pub fn watch(&mut self, path: impl Into<PathBuf>) {
+        self.files
+            .entry(path.into())
+            .or_insert_with_key(|path| modified(path));
+    }
+
+    pub fn watch_all(&mut self, paths: impl IntoIterator<Item = impl Into<PathBuf>>) {
+        for path in paths {
+            self.watch(path);
+        }
+    }
+
+    /// Returns true if any watched file was modified, created or removed since the last call
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, last_modified) in &mut self.files {
+            let modified = modified(path);
+            if modified != *last_modified {
+                *last_modified = modified;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -7,10 +7,11 @@ frei0r_rs2::plugin!(FilterPlugin);
 
 #[cfg(test)]
 mod tests {
-    use std::ffi::c_void;
+    use std::ffi::{CStr, CString, c_char, c_void};
 
     use super::*;
     use test_support::{HEIGHT, WIDTH, assert_output, param_cstring, read_image_u32};
+    use webvfx::param_index;
 
     #[test]
     fn test_filter() {
@@ -18,11 +19,121 @@ mod tests {
         let html_path = param_cstring("filter.html");
         let html_ptr = html_path.as_ptr();
         let html_param = &raw const html_ptr as *mut c_void;
-        f0r_set_param_value(plugin, html_param, 0);
+        f0r_set_param_value(plugin, html_param, param_index::HTML_PATH);
         let mut output = vec![0u32; (WIDTH * HEIGHT) as usize];
         let inframe = read_image_u32("a-320x240.png");
         unsafe { f0r_update(plugin, 0.0, inframe.as_ptr(), output.as_mut_ptr()) };
         assert_output("filter-1.png", &output);
         f0r_destruct(plugin);
     }
+
+    #[test]
+    fn test_filter_html_path_changed() {
+        let plugin = f0r_construct(WIDTH, HEIGHT);
+        let html_path = param_cstring("nonexistent.html");
+        let html_ptr = html_path.as_ptr();
+        let html_param = &raw const html_ptr as *mut c_void;
+        f0r_set_param_value(plugin, html_param, param_index::HTML_PATH);
+        let mut output = vec![0u32; (WIDTH * HEIGHT) as usize];
+        let inframe = read_image_u32("a-320x240.png");
+        unsafe { f0r_update(plugin, 0.0, inframe.as_ptr(), output.as_mut_ptr()) };
+
+        let html_path = param_cstring("filter.html");
+        let html_ptr = html_path.as_ptr();
+        let html_param = &raw const html_ptr as *mut c_void;
+        f0r_set_param_value(plugin, html_param, param_index::HTML_PATH);
+        unsafe { f0r_update(plugin, 0.0, inframe.as_ptr(), output.as_mut_ptr()) };
+        assert_output("filter-1.png", &output);
+        f0r_destruct(plugin);
+    }
+
+    #[test]
+    fn test_filter_error_passthrough() {
+        let plugin = f0r_construct(WIDTH, HEIGHT);
+        let on_error = CString::new("passthrough").unwrap();
+        let on_error_ptr = on_error.as_ptr();
+        let on_error_param = &raw const on_error_ptr as *mut c_void;
+        f0r_set_param_value(plugin, on_error_param, param_index::ON_ERROR);
+        let html_path = param_cstring("nonexistent.html");
+        let html_ptr = html_path.as_ptr();
+        let html_param = &raw const html_ptr as *mut c_void;
+        f0r_set_param_value(plugin, html_param, param_index::HTML_PATH);
+        let mut output = vec![0u32; (WIDTH * HEIGHT) as usize];
+        let inframe = read_image_u32("a-320x240.png");
+        unsafe { f0r_update(plugin, 0.0, inframe.as_ptr(), output.as_mut_ptr()) };
+        assert_eq!(output, inframe);
+        f0r_destruct(plugin);
+    }
+
+    #[test]
+    fn test_filter_status() {
+        let plugin = f0r_construct(WIDTH, HEIGHT);
+        let html_path = param_cstring("nonexistent.html");
+        let html_ptr = html_path.as_ptr();
+        let html_param = &raw const html_ptr as *mut c_void;
+        f0r_set_param_value(plugin, html_param, param_index::HTML_PATH);
+        let mut output = vec![0u32; (WIDTH * HEIGHT) as usize];
+        let inframe = read_image_u32("a-320x240.png");
+        unsafe { f0r_update(plugin, 0.0, inframe.as_ptr(), output.as_mut_ptr()) };
+
+        let get_string_param = |index| {
+            let mut value: *const c_char = std::ptr::null();
+            f0r_get_param_value(plugin, &raw mut value as *mut c_void, index);
+            unsafe { CStr::from_ptr(value) }
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(get_string_param(param_index::STATUS), "failed");
+        assert!(get_string_param(param_index::LAST_ERROR).contains("nonexistent.html"));
+
+        let html_path = param_cstring("filter.html");
+        let html_ptr = html_path.as_ptr();
+        let html_param = &raw const html_ptr as *mut c_void;
+        f0r_set_param_value(plugin, html_param, param_index::HTML_PATH);
+        unsafe { f0r_update(plugin, 0.0, inframe.as_ptr(), output.as_mut_ptr()) };
+        assert_eq!(
+            get_string_param(param_index::STATUS),
+            "ready, img.webvfx-video1 found"
+        );
+        assert_eq!(get_string_param(param_index::LAST_ERROR), "");
+        f0r_destruct(plugin);
+    }
+
+    #[test]
+    fn test_filter_retry_when_html_created() {
+        let dir = std::env::temp_dir().join(format!("webvfx-filter-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let html_path = dir.join("created.html");
+        let _ = std::fs::remove_file(&html_path);
+
+        let plugin = f0r_construct(WIDTH, HEIGHT);
+        let html_path_param = CString::new(html_path.to_str().unwrap()).unwrap();
+        let html_ptr = html_path_param.as_ptr();
+        let html_param = &raw const html_ptr as *mut c_void;
+        f0r_set_param_value(plugin, html_param, param_index::HTML_PATH);
+        let mut output = vec![0u32; (WIDTH * HEIGHT) as usize];
+        let inframe = read_image_u32("a-320x240.png");
+        unsafe { f0r_update(plugin, 0.0, inframe.as_ptr(), output.as_mut_ptr()) };
+
+        let get_string_param = |index| {
+            let mut value: *const c_char = std::ptr::null();
+            f0r_get_param_value(plugin, &raw mut value as *mut c_void, index);
+            unsafe { CStr::from_ptr(value) }
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(get_string_param(param_index::STATUS), "failed");
+
+        // Creating the file retries without any param changing
+        std::fs::write(&html_path, r#"<img class="webvfx-video1">"#).unwrap();
+        unsafe { f0r_update(plugin, 0.04, inframe.as_ptr(), output.as_mut_ptr()) };
+        assert_eq!(
+            get_string_param(param_index::STATUS),
+            "ready, img.webvfx-video1 found"
+        );
+        f0r_destruct(plugin);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
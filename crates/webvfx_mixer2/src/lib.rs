@@ -11,6 +11,7 @@ mod tests {
 
     use super::*;
     use test_support::{HEIGHT, WIDTH, assert_output, param_cstring, read_image_u32};
+    use webvfx::param_index;
 
     #[test]
     fn test_mixer2() {
@@ -18,7 +19,7 @@ mod tests {
         let html_path = param_cstring("mixer2.html");
         let html_ptr = html_path.as_ptr();
         let html_param = &raw const html_ptr as *mut c_void;
-        f0r_set_param_value(plugin, html_param, 0);
+        f0r_set_param_value(plugin, html_param, param_index::HTML_PATH);
         let mut output = vec![0u32; (WIDTH * HEIGHT) as usize];
         let inframe1 = read_image_u32("a-320x240.png");
         let inframe2 = read_image_u32("b-320x240.png");
@@ -35,4 +36,41 @@ mod tests {
         assert_output("mixer2-1.png", &output);
         f0r_destruct(plugin);
     }
+
+    #[test]
+    fn test_mixer2_scrub_animations() {
+        let plugin = f0r_construct(WIDTH, HEIGHT);
+        let html_path = param_cstring("mixer2.html");
+        let html_ptr = html_path.as_ptr();
+        let html_param = &raw const html_ptr as *mut c_void;
+        f0r_set_param_value(plugin, html_param, param_index::HTML_PATH);
+        let mut position = 0.0f64;
+        f0r_set_param_value(
+            plugin,
+            &raw mut position as *mut c_void,
+            param_index::mixer2::POSITION,
+        );
+        let mut scrub = 1.0f64;
+        f0r_set_param_value(
+            plugin,
+            &raw mut scrub as *mut c_void,
+            param_index::mixer2::SCRUB_ANIMATIONS,
+        );
+        let mut output = vec![0u32; (WIDTH * HEIGHT) as usize];
+        let inframe1 = read_image_u32("a-320x240.png");
+        let inframe2 = read_image_u32("b-320x240.png");
+        // Animations are driven by position, so this renders the same frame as time 0
+        unsafe {
+            f0r_update2(
+                plugin,
+                2.0,
+                inframe1.as_ptr(),
+                inframe2.as_ptr(),
+                std::ptr::null(),
+                output.as_mut_ptr(),
+            );
+        }
+        assert_output("mixer2-1.png", &output);
+        f0r_destruct(plugin);
+    }
 }
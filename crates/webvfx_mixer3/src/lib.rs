@@ -11,6 +11,7 @@ mod tests {
 
     use super::*;
     use test_support::{HEIGHT, WIDTH, assert_output, param_cstring, read_image_u32};
+    use webvfx::param_index;
 
     #[test]
     fn test_mixer3() {
@@ -18,7 +19,7 @@ mod tests {
         let html_path = param_cstring("mixer3.html");
         let html_ptr = html_path.as_ptr();
         let html_param = &raw const html_ptr as *mut c_void;
-        f0r_set_param_value(plugin, html_param, 0);
+        f0r_set_param_value(plugin, html_param, param_index::HTML_PATH);
         let mut output = vec![0u32; (WIDTH * HEIGHT) as usize];
         let inframe1 = read_image_u32("a-320x240.png");
         let inframe2 = read_image_u32("b-320x240.png");
@@ -7,10 +7,14 @@ frei0r_rs2::plugin!(SourcePlugin);
 
 #[cfg(test)]
 mod tests {
-    use std::{ffi::c_void, ptr};
+    use std::{
+        ffi::{CString, c_void},
+        ptr,
+    };
 
     use super::*;
-    use test_support::{HEIGHT, WIDTH, assert_output, param_cstring};
+    use test_support::{HEIGHT, WIDTH, assert_output, param_cstring, testdata};
+    use webvfx::param_index;
 
     #[test]
     fn test_source() {
@@ -18,10 +22,62 @@ mod tests {
         let html_path = param_cstring("source.html");
         let html_ptr = html_path.as_ptr();
         let html_param = &raw const html_ptr as *mut c_void;
-        f0r_set_param_value(plugin, html_param, 0);
+        f0r_set_param_value(plugin, html_param, param_index::HTML_PATH);
         let mut output = vec![0u32; (WIDTH * HEIGHT) as usize];
         unsafe { f0r_update(plugin, 0.0, ptr::null::<u32>(), output.as_mut_ptr()) };
         assert_output("source-1.png", &output);
         f0r_destruct(plugin);
     }
+
+    #[test]
+    fn test_source_html_source() {
+        let plugin = f0r_construct(WIDTH, HEIGHT);
+        let html_source =
+            CString::new(std::fs::read_to_string(testdata!().join("source.html")).unwrap())
+                .unwrap();
+        let html_ptr = html_source.as_ptr();
+        let html_param = &raw const html_ptr as *mut c_void;
+        f0r_set_param_value(plugin, html_param, param_index::HTML_SOURCE);
+        let base_url = param_cstring("");
+        let base_url_ptr = base_url.as_ptr();
+        let base_url_param = &raw const base_url_ptr as *mut c_void;
+        f0r_set_param_value(plugin, base_url_param, param_index::BASE_URL);
+        let mut output = vec![0u32; (WIDTH * HEIGHT) as usize];
+        unsafe { f0r_update(plugin, 0.0, ptr::null::<u32>(), output.as_mut_ptr()) };
+        assert_output("source-1.png", &output);
+        f0r_destruct(plugin);
+    }
+
+    #[test]
+    fn test_source_declared_defaults() {
+        let plugin = f0r_construct(WIDTH, HEIGHT);
+        let html_source = CString::new(
+            r##"<meta name="webvfx-param" content="name=opacity;default=0.5">
+            <meta name="webvfx-param" content="name=tint;type=color;default=#f00">"##,
+        )
+        .unwrap();
+        let html_ptr = html_source.as_ptr();
+        let html_param = &raw const html_ptr as *mut c_void;
+        f0r_set_param_value(plugin, html_param, param_index::HTML_SOURCE);
+        let mut output = vec![0u32; (WIDTH * HEIGHT) as usize];
+        unsafe { f0r_update(plugin, 0.0, ptr::null::<u32>(), output.as_mut_ptr()) };
+
+        let mut param1 = 0.0f64;
+        f0r_get_param_value(plugin, &raw mut param1 as *mut c_void, param_index::PARAM1);
+        assert!((param1 - 0.5).abs() < f64::EPSILON);
+        let mut color1 = frei0r_rs2::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+        };
+        f0r_get_param_value(plugin, &raw mut color1 as *mut c_void, param_index::COLOR1);
+        assert!((color1.r - 1.0).abs() < f32::EPSILON && color1.g.abs() < f32::EPSILON);
+
+        // Values the host sets take precedence
+        let mut value = 0.25f64;
+        f0r_set_param_value(plugin, &raw mut value as *mut c_void, param_index::PARAM1);
+        f0r_get_param_value(plugin, &raw mut param1 as *mut c_void, param_index::PARAM1);
+        assert!((param1 - 0.25).abs() < f64::EPSILON);
+        f0r_destruct(plugin);
+    }
 }